@@ -0,0 +1,333 @@
+/*
+
+Runtime overrides for a component's `params`. The file is a flat TOML or JSON
+table of param name to value; the generated `<Component>Config::from_overrides`
+decides which keys exist and what type each one has. The reserved `disabled`
+key lists signals and rules to switch off (see `RuleMask`), and `profile`
+picks one of the component's score profiles.
+
+*/
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamValue {
+    Float(f64),
+    Int(i64),
+    Bool(bool),
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Parse(String),
+    /// Key is not a param of this component.
+    UnknownParam(String),
+    /// `disabled` names a signal or rule this component doesn't have.
+    UnknownRule(String),
+    /// `profile` names a profile this component doesn't declare.
+    UnknownProfile(String),
+    WrongType {
+        param: String,
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "cannot read config: {}", err),
+            ConfigError::Parse(err) => write!(f, "cannot parse config: {}", err),
+            ConfigError::UnknownParam(param) => write!(f, "unknown param `{}`", param),
+            ConfigError::UnknownRule(rule) => write!(f, "unknown signal or rule `{}`", rule),
+            ConfigError::UnknownProfile(profile) => write!(f, "unknown profile `{}`", profile),
+            ConfigError::WrongType { param, expected } => {
+                write!(f, "param `{}` must be `{}`", param, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, Default, Clone)]
+pub struct ParamOverrides {
+    pub values: BTreeMap<String, ParamValue>,
+    /// Signals (`format.pe_magic`) and rules (`PE-001`) to disable.
+    pub disabled: Vec<String>,
+    /// Score profile (`email`), or the clinch values when unset.
+    pub profile: Option<String>,
+}
+
+/// Reserved key for the rule mask.
+const DISABLED_KEY: &str = "disabled";
+
+/// Reserved key for the score profile.
+const PROFILE_KEY: &str = "profile";
+
+impl ParamOverrides {
+    /// Load `.json` as JSON, anything else as TOML.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json_str(&text)
+        } else {
+            Self::from_toml_str(&text)
+        }
+    }
+
+    pub fn from_toml_str(text: &str) -> Result<Self, ConfigError> {
+        let table: toml::Table = text
+            .parse()
+            .map_err(|err: toml::de::Error| ConfigError::Parse(err.to_string()))?;
+
+        let mut values = BTreeMap::new();
+        let mut disabled = Vec::new();
+        let mut profile = None;
+        for (key, value) in table {
+            let value = match value {
+                toml::Value::String(name) if key == PROFILE_KEY => {
+                    profile = Some(name);
+                    continue;
+                }
+                toml::Value::Array(names) if key == DISABLED_KEY => {
+                    for name in names {
+                        match name {
+                            toml::Value::String(name) => disabled.push(name),
+                            _ => return Err(disabled_not_strings()),
+                        }
+                    }
+                    continue;
+                }
+                toml::Value::Float(v) => ParamValue::Float(v),
+                toml::Value::Integer(v) => ParamValue::Int(v),
+                toml::Value::Boolean(v) => ParamValue::Bool(v),
+                other => {
+                    return Err(ConfigError::Parse(format!(
+                        "`{}` must be a number or bool, found {}",
+                        key,
+                        other.type_str()
+                    )));
+                }
+            };
+            values.insert(key, value);
+        }
+
+        Ok(Self {
+            values,
+            disabled,
+            profile,
+        })
+    }
+
+    pub fn from_json_str(text: &str) -> Result<Self, ConfigError> {
+        let json: serde_json::Value =
+            serde_json::from_str(text).map_err(|err| ConfigError::Parse(err.to_string()))?;
+
+        let serde_json::Value::Object(object) = json else {
+            return Err(ConfigError::Parse("expected a JSON object".to_string()));
+        };
+
+        let mut values = BTreeMap::new();
+        let mut disabled = Vec::new();
+        let mut profile = None;
+        for (key, value) in object {
+            let value = match value {
+                serde_json::Value::String(name) if key == PROFILE_KEY => {
+                    profile = Some(name);
+                    continue;
+                }
+                serde_json::Value::Array(names) if key == DISABLED_KEY => {
+                    for name in names {
+                        match name {
+                            serde_json::Value::String(name) => disabled.push(name),
+                            _ => return Err(disabled_not_strings()),
+                        }
+                    }
+                    continue;
+                }
+                serde_json::Value::Bool(v) => ParamValue::Bool(v),
+                serde_json::Value::Number(n) if n.is_i64() => {
+                    ParamValue::Int(n.as_i64().unwrap_or_default())
+                }
+                serde_json::Value::Number(n) => ParamValue::Float(n.as_f64().unwrap_or_default()),
+                _ => {
+                    return Err(ConfigError::Parse(format!(
+                        "`{}` must be a number or bool",
+                        key
+                    )));
+                }
+            };
+            values.insert(key, value);
+        }
+
+        Ok(Self {
+            values,
+            disabled,
+            profile,
+        })
+    }
+}
+
+impl ParamValue {
+    /// Integers are accepted where a float is expected (`HIGH_ENTROPY = 7`).
+    pub fn as_f64(&self, param: &str) -> Result<f64, ConfigError> {
+        match self {
+            ParamValue::Float(v) => Ok(*v),
+            ParamValue::Int(v) => Ok(*v as f64),
+            ParamValue::Bool(_) => Err(wrong_type(param, "f64")),
+        }
+    }
+
+    pub fn as_i64(&self, param: &str) -> Result<i64, ConfigError> {
+        match self {
+            ParamValue::Int(v) => Ok(*v),
+            _ => Err(wrong_type(param, "i64")),
+        }
+    }
+
+    pub fn as_u64(&self, param: &str) -> Result<u64, ConfigError> {
+        match self {
+            ParamValue::Int(v) => u64::try_from(*v).map_err(|_| wrong_type(param, "u64")),
+            _ => Err(wrong_type(param, "u64")),
+        }
+    }
+
+    pub fn as_usize(&self, param: &str) -> Result<usize, ConfigError> {
+        match self {
+            ParamValue::Int(v) => usize::try_from(*v).map_err(|_| wrong_type(param, "usize")),
+            _ => Err(wrong_type(param, "usize")),
+        }
+    }
+
+    pub fn as_bool(&self, param: &str) -> Result<bool, ConfigError> {
+        match self {
+            ParamValue::Bool(v) => Ok(*v),
+            _ => Err(wrong_type(param, "bool")),
+        }
+    }
+}
+
+fn wrong_type(param: &str, expected: &'static str) -> ConfigError {
+    ConfigError::WrongType {
+        param: param.to_string(),
+        expected,
+    }
+}
+
+fn disabled_not_strings() -> ConfigError {
+    ConfigError::Parse(format!("`{}` must be a list of names", DISABLED_KEY))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_reads_params_disabled_and_profile() {
+        let overrides = ParamOverrides::from_toml_str(
+            r#"
+            HIGH_ENTROPY = 7.5
+            MAX_SIZE = 1024
+            STRICT = true
+            disabled = ["format.pe_magic", "PDF-001"]
+            profile = "email"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            overrides.values,
+            BTreeMap::from([
+                (String::from("HIGH_ENTROPY"), ParamValue::Float(7.5)),
+                (String::from("MAX_SIZE"), ParamValue::Int(1024)),
+                (String::from("STRICT"), ParamValue::Bool(true)),
+            ])
+        );
+        assert_eq!(overrides.disabled, ["format.pe_magic", "PDF-001"]);
+        assert_eq!(overrides.profile.as_deref(), Some("email"));
+    }
+
+    #[test]
+    fn json_reads_params_disabled_and_profile() {
+        let overrides = ParamOverrides::from_json_str(
+            r#"{
+                "HIGH_ENTROPY": 7.5,
+                "MAX_SIZE": 1024,
+                "STRICT": false,
+                "disabled": ["format.pe_magic"],
+                "profile": "email"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            overrides.values,
+            BTreeMap::from([
+                (String::from("HIGH_ENTROPY"), ParamValue::Float(7.5)),
+                (String::from("MAX_SIZE"), ParamValue::Int(1024)),
+                (String::from("STRICT"), ParamValue::Bool(false)),
+            ])
+        );
+        assert_eq!(overrides.disabled, ["format.pe_magic"]);
+        assert_eq!(overrides.profile.as_deref(), Some("email"));
+    }
+
+    #[test]
+    fn empty_file_overrides_nothing() {
+        let overrides = ParamOverrides::from_toml_str("").unwrap();
+
+        assert!(overrides.values.is_empty());
+        assert!(overrides.disabled.is_empty());
+        assert_eq!(overrides.profile, None);
+    }
+
+    #[test]
+    fn disabled_must_list_names() {
+        for result in [
+            ParamOverrides::from_toml_str("disabled = [1]"),
+            ParamOverrides::from_json_str(r#"{ "disabled": [true] }"#),
+        ] {
+            assert!(matches!(
+                result,
+                Err(ConfigError::Parse(message)) if message == "`disabled` must be a list of names"
+            ));
+        }
+    }
+
+    #[test]
+    fn string_param_is_rejected() {
+        assert!(matches!(
+            ParamOverrides::from_toml_str(r#"HIGH_ENTROPY = "high""#),
+            Err(ConfigError::Parse(_))
+        ));
+        assert!(matches!(
+            ParamOverrides::from_json_str(r#"{ "HIGH_ENTROPY": "high" }"#),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn json_must_be_an_object() {
+        assert!(matches!(
+            ParamOverrides::from_json_str("[1, 2]"),
+            Err(ConfigError::Parse(message)) if message == "expected a JSON object"
+        ));
+    }
+
+    #[test]
+    fn values_convert_to_the_param_type() {
+        assert_eq!(ParamValue::Int(7).as_f64("HIGH_ENTROPY").unwrap(), 7.0);
+        assert_eq!(ParamValue::Int(7).as_usize("MAX_SIZE").unwrap(), 7);
+        assert!(ParamValue::Int(-1).as_u64("MAX_SIZE").is_err());
+        assert!(matches!(
+            ParamValue::Float(7.5).as_i64("COUNT"),
+            Err(ConfigError::WrongType { param, expected: "i64" }) if param == "COUNT"
+        ));
+        assert!(ParamValue::Bool(true).as_f64("HIGH_ENTROPY").is_err());
+    }
+}
@@ -1,5 +1,9 @@
 use std::collections::HashMap;
 
+/// Runtime guard for `run pipeline`. Cycles across components can't be seen by
+/// a single `lunch!` invocation, so nesting stops at this depth.
+pub const MAX_PIPELINE_DEPTH: usize = 8;
+
 #[derive(Debug)]
 pub struct Artifact {
     /// Raw bytes of the artifact (file, payload, stream, etc.)
@@ -12,12 +16,140 @@ pub struct Artifact {
     pub meta: HashMap<String, String>,
 }
 
+/// Final decision for an artifact.
+///
+/// Variants are ordered by severity, so the highest verdict raised wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Verdict {
+    Clean,
+    Suspicious,
+    Malicious,
+}
+
+/// Data captured from the fired signal's bindings when an emit carries a payload.
+#[derive(Debug, Clone)]
+pub struct EmitPayload {
+    pub event: String,
+    pub fields: Vec<(String, String)>,
+}
+
+/// Links a clinch action back to the rule (or anonymous clause) that produced it.
+#[derive(Debug, Clone)]
+pub struct Attribution {
+    /// Rule ID, e.g. `PDF-001`, or `signal.<family>.<name>[#<n>]` for anonymous clauses.
+    pub rule: String,
+    /// The action as written in the DSL, e.g. `score risk += 1.0`. A tag action
+    /// holds the tag it pushed instead, e.g. `format:pdf`.
+    pub action: String,
+}
+
+/// Explain-mode record of one clinch action: which clause ran it, what fired the
+/// clause, and what the action changed.
+#[derive(Debug, Clone)]
+pub struct Provenance {
+    /// Rule ID, or `signal.<family>.<name>[#<n>]` for anonymous clauses.
+    pub clause: String,
+    /// Signals that fired the clause, e.g. `format.pdf_magic`.
+    pub signals: Vec<String>,
+    /// Operation step the signals derive from, e.g. `magic.inspect_magic`.
+    pub step: String,
+    /// Same as `Attribution::action`.
+    pub action: String,
+    /// Set for `score` actions.
+    pub score: Option<ScoreChange>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScoreChange {
+    pub key: String,
+    pub before: f64,
+    pub after: f64,
+}
+
+/// Bounds for recursive scanning of child artifacts (`do ... output ... children`).
+/// Counts and sizes cover the whole tree below the top-level artifact.
+#[derive(Debug, Clone, Copy)]
+pub struct ScanLimits {
+    pub max_depth: usize,
+    pub max_children: usize,
+    pub max_total_bytes: usize,
+}
+
+impl Default for ScanLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_children: 256,
+            max_total_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Context of a child artifact scanned from its parent.
+#[derive(Debug)]
+pub struct ChildContext {
+    /// Step that produced the child, e.g. `archive.zip_extract`.
+    pub source: String,
+    /// `name` from the child's metadata, when the op provided one.
+    pub name: Option<String>,
+    pub context: LuciusContext,
+}
+
 #[derive(Debug, Default)]
 pub struct LuciusContext {
     pub tags: Vec<String>,
     pub emits: Vec<String>,
     pub deferred: Vec<String>,
     pub scores: HashMap<String, f64>,
+
+    /// Payloads for emits declared as `emit Event { field = binding.field }`.
+    pub emit_payloads: Vec<EmitPayload>,
+
+    /// Suppressions that took effect, e.g. `signal.format.pe_magic` or `tag:type:pe`.
+    pub suppressed: Vec<String>,
+
+    /// Tags allowlisted for this artifact; `push_tag` drops them.
+    pub suppressed_tags: Vec<String>,
+
+    /// One entry per clinch action that ran, in execution order.
+    pub attributions: Vec<Attribution>,
+
+    /// When set, each clinch action appends a `Provenance` record. Off by default.
+    pub explain_mode: bool,
+
+    /// Explain-mode trail, in execution order. See `explain()`.
+    pub provenance: Vec<Provenance>,
+
+    /// Highest verdict raised by any clinch clause.
+    pub verdict: Option<Verdict>,
+
+    /// Set by `halt`; remaining clinch clauses are skipped.
+    pub halted: bool,
+
+    /// Nesting level; `0` for a top-level run.
+    pub depth: usize,
+
+    /// Limits for child scanning; inherited by nested contexts.
+    pub limits: ScanLimits,
+
+    /// Child artifacts scanned below this one.
+    pub children: Vec<ChildContext>,
+
+    /// Children not scanned because a limit was hit, with the reason.
+    pub skipped_children: Vec<String>,
+
+    /// Children and bytes scanned anywhere below this context.
+    pub scanned_children: usize,
+    pub scanned_bytes: usize,
+
+    /// Signals and rules switched off by the config's `RuleMask`.
+    pub disabled_rules: Vec<String>,
+
+    /// Gated stages whose gate signal did not fire.
+    pub skipped_stages: Vec<String>,
+
+    /// `run pipeline` targets skipped because `MAX_PIPELINE_DEPTH` was reached.
+    pub skipped_pipelines: Vec<String>,
 }
 impl LuciusContext {
     pub fn new() -> Self {
@@ -26,6 +158,388 @@ impl LuciusContext {
             emits: Vec::new(),
             deferred: Vec::new(),
             scores: HashMap::new(),
+            emit_payloads: Vec::new(),
+            suppressed: Vec::new(),
+            suppressed_tags: Vec::new(),
+            attributions: Vec::new(),
+            explain_mode: false,
+            provenance: Vec::new(),
+            verdict: None,
+            halted: false,
+            depth: 0,
+            limits: ScanLimits::default(),
+            children: Vec::new(),
+            skipped_children: Vec::new(),
+            scanned_children: 0,
+            scanned_bytes: 0,
+            disabled_rules: Vec::new(),
+            skipped_stages: Vec::new(),
+            skipped_pipelines: Vec::new(),
+        }
+    }
+
+    /// Context with explain mode on, for `run_<component>_pipeline_in`.
+    pub fn explained() -> Self {
+        Self {
+            explain_mode: true,
+            ..Self::new()
+        }
+    }
+
+    /// Fresh context for a nested run one level down. Explain mode carries over,
+    /// and the limits are narrowed to whatever budget is left here.
+    pub fn nested(&self) -> Self {
+        let mut limits = self.limits;
+        limits.max_children = limits.max_children.saturating_sub(self.scanned_children);
+        limits.max_total_bytes = limits.max_total_bytes.saturating_sub(self.scanned_bytes);
+
+        Self {
+            explain_mode: self.explain_mode,
+            depth: self.depth + 1,
+            limits,
+            ..Self::new()
+        }
+    }
+
+    /// Why `child` can't be scanned from here, if a limit is in the way.
+    pub fn child_limit_reached(&self, child: &Artifact) -> Option<String> {
+        if self.depth >= self.limits.max_depth {
+            return Some(format!("max depth {} reached", self.limits.max_depth));
+        }
+
+        if self.scanned_children >= self.limits.max_children {
+            return Some(format!("max children {} reached", self.limits.max_children));
+        }
+
+        let remaining = self
+            .limits
+            .max_total_bytes
+            .saturating_sub(self.scanned_bytes);
+        if child.bytes.len() > remaining {
+            return Some(format!(
+                "{} byte child exceeds remaining budget of {} bytes",
+                child.bytes.len(),
+                remaining
+            ));
+        }
+
+        None
+    }
+
+    /// Context for scanning `child`. Its limits are narrowed to whatever budget
+    /// is left here so grandchildren count against the same totals.
+    pub fn child_scan(&self, child: &Artifact) -> Self {
+        let mut ctx = self.nested();
+        ctx.limits.max_children -= 1;
+        ctx.limits.max_total_bytes -= child.bytes.len();
+        ctx
+    }
+
+    /// Attach a scanned child's context and account for everything it scanned.
+    pub fn attach_child(&mut self, source: &str, context: LuciusContext, child: &Artifact) {
+        self.scanned_children += 1 + context.scanned_children;
+        self.scanned_bytes += child.bytes.len() + context.scanned_bytes;
+
+        self.children.push(ChildContext {
+            source: source.to_string(),
+            name: child.meta.get("name").cloned(),
+            context,
+        });
+    }
+
+    /// Fold a nested pipeline's context into this one, prefixing everything it
+    /// produced with `<namespace>.`, down to its children's children. Its verdict
+    /// is raised here as well, and whatever it scanned counts against this
+    /// context's limits.
+    pub fn merge_namespaced(&mut self, namespace: &str, nested: LuciusContext) {
+        let nested = nested.namespaced(namespace);
+
+        self.tags.extend(nested.tags);
+        self.emits.extend(nested.emits);
+        self.deferred.extend(nested.deferred);
+        self.scores.extend(nested.scores);
+        self.emit_payloads.extend(nested.emit_payloads);
+        self.suppressed.extend(nested.suppressed);
+        self.attributions.extend(nested.attributions);
+        self.provenance.extend(nested.provenance);
+        self.disabled_rules.extend(nested.disabled_rules);
+        self.skipped_stages.extend(nested.skipped_stages);
+        self.skipped_pipelines.extend(nested.skipped_pipelines);
+        self.children.extend(nested.children);
+        self.skipped_children.extend(nested.skipped_children);
+        self.scanned_children += nested.scanned_children;
+        self.scanned_bytes += nested.scanned_bytes;
+
+        if let Some(verdict) = nested.verdict {
+            self.raise_verdict(verdict);
+        }
+    }
+
+    /// Every name this context and its children produced, prefixed with
+    /// `<namespace>.`. A child keeps its own context's shape, so its records
+    /// are prefixed the same way.
+    fn namespaced(self, namespace: &str) -> Self {
+        let ns = |value: String| format!("{}.{}", namespace, value);
+
+        Self {
+            tags: self.tags.into_iter().map(ns).collect(),
+            emits: self.emits.into_iter().map(ns).collect(),
+            deferred: self.deferred.into_iter().map(ns).collect(),
+            scores: self.scores.into_iter().map(|(k, v)| (ns(k), v)).collect(),
+            emit_payloads: self
+                .emit_payloads
+                .into_iter()
+                .map(|p| EmitPayload {
+                    event: ns(p.event),
+                    ..p
+                })
+                .collect(),
+            suppressed: self.suppressed.into_iter().map(ns).collect(),
+            attributions: self
+                .attributions
+                .into_iter()
+                .map(|a| Attribution {
+                    rule: ns(a.rule),
+                    ..a
+                })
+                .collect(),
+            provenance: self
+                .provenance
+                .into_iter()
+                .map(|p| Provenance {
+                    clause: ns(p.clause),
+                    ..p
+                })
+                .collect(),
+            disabled_rules: self.disabled_rules.into_iter().map(ns).collect(),
+            skipped_stages: self.skipped_stages.into_iter().map(ns).collect(),
+            skipped_pipelines: self.skipped_pipelines.into_iter().map(ns).collect(),
+            children: self
+                .children
+                .into_iter()
+                .map(|child| ChildContext {
+                    source: ns(child.source),
+                    context: child.context.namespaced(namespace),
+                    ..child
+                })
+                .collect(),
+            skipped_children: self.skipped_children.into_iter().map(ns).collect(),
+            ..self
+        }
+    }
+
+    /// Current value of a score, `0.0` when unset.
+    pub fn score(&self, key: &str) -> f64 {
+        self.scores.get(key).copied().unwrap_or(0.0)
+    }
+
+    /// Append a provenance record when explain mode is on. `score` carries the
+    /// key and its value before the action ran.
+    pub fn record(
+        &mut self,
+        clause: &str,
+        signals: &[&str],
+        step: &str,
+        action: &str,
+        score: Option<(&str, f64)>,
+    ) {
+        if !self.explain_mode {
+            return;
         }
+
+        let score = score.map(|(key, before)| ScoreChange {
+            key: key.to_string(),
+            before,
+            after: self.score(key),
+        });
+
+        self.provenance.push(Provenance {
+            clause: clause.to_string(),
+            signals: signals.iter().map(|s| s.to_string()).collect(),
+            step: step.to_string(),
+            action: action.to_string(),
+            score,
+        });
+    }
+
+    /// Render the explain-mode trail as text, one clause per paragraph.
+    pub fn explain(&self) -> String {
+        let mut out = String::new();
+        let mut current: Option<&str> = None;
+
+        for record in &self.provenance {
+            if current != Some(record.clause.as_str()) {
+                if current.is_some() {
+                    out.push('\n');
+                }
+                out.push_str(&format!(
+                    "{} (signal {} <- operation {})\n",
+                    record.clause,
+                    record.signals.join(", "),
+                    record.step
+                ));
+                current = Some(record.clause.as_str());
+            }
+
+            out.push_str(&format!("  {}", record.action));
+            if let Some(change) = &record.score {
+                out.push_str(&format!(
+                    "  [{}: {} -> {}]",
+                    change.key, change.before, change.after
+                ));
+            }
+            out.push('\n');
+        }
+
+        if !self.scores.is_empty() {
+            let mut keys: Vec<&String> = self.scores.keys().collect();
+            keys.sort();
+
+            out.push_str("\nfinal scores:\n");
+            for key in keys {
+                out.push_str(&format!("  {} = {}\n", key, self.scores[key]));
+            }
+        }
+
+        out
+    }
+
+    /// Push a tag unless it has been suppressed, in which case record the suppression.
+    pub fn push_tag(&mut self, tag: String) {
+        if self.suppressed_tags.contains(&tag) {
+            self.suppressed.push(format!("tag:{}", tag));
+        } else {
+            self.tags.push(tag);
+        }
+    }
+
+    /// Record that `rule` produced `action`.
+    pub fn attribute(&mut self, rule: &str, action: &str) {
+        self.attributions.push(Attribution {
+            rule: rule.to_string(),
+            action: action.to_string(),
+        });
+    }
+
+    /// Fold `key` from the direct children into this context. Children have
+    /// already rolled up their own children, so this covers the whole tree.
+    /// Nothing changes when no child has the score. `fold` is the `rollup`
+    /// function (`f64::max`, `f64::min` or a sum).
+    pub fn rollup_score(&mut self, key: &str, fold: fn(f64, f64) -> f64) {
+        let mut values = self
+            .children
+            .iter()
+            .filter_map(|child| child.context.scores.get(key).copied());
+
+        let Some(first) = values.next() else {
+            return;
+        };
+
+        let rolled = values.fold(first, fold);
+        let value = match self.scores.get(key).copied() {
+            Some(current) => fold(current, rolled),
+            None => rolled,
+        };
+
+        self.scores.insert(key.to_string(), value);
+    }
+
+    /// Union of child tags, each prefixed and each once. Suppressions still apply.
+    pub fn rollup_tags(&mut self, prefix: &str) {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.children.iter().flat_map(|child| &child.context.tags) {
+            let tag = format!("{}{}", prefix, tag);
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        for tag in tags {
+            if !self.tags.contains(&tag) {
+                self.push_tag(tag);
+            }
+        }
+    }
+
+    /// Raise this context's verdict to the highest child verdict.
+    pub fn rollup_verdict(&mut self) {
+        let highest = self
+            .children
+            .iter()
+            .filter_map(|child| child.context.verdict)
+            .max();
+
+        if let Some(verdict) = highest {
+            self.raise_verdict(verdict);
+        }
+    }
+
+    /// Raise the verdict, keeping whichever of the current and new one is more severe.
+    pub fn raise_verdict(&mut self, verdict: Verdict) {
+        if self.verdict.is_none_or(|current| verdict > current) {
+            self.verdict = Some(verdict);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact() -> Artifact {
+        Artifact {
+            bytes: vec![0],
+            text: None,
+            meta: HashMap::new(),
+        }
+    }
+
+    /// A context holding `tag` that scanned one child, which holds `child_tag`.
+    fn with_child(tag: &str, child_tag: &str) -> LuciusContext {
+        let mut child = LuciusContext::new();
+        child.tags.push(child_tag.to_string());
+        child
+            .skipped_children
+            .push("parts.split: max depth 4 reached".to_string());
+
+        let mut ctx = LuciusContext::new();
+        ctx.tags.push(tag.to_string());
+        ctx.attach_child("parts.split", child, &artifact());
+        ctx
+    }
+
+    #[test]
+    fn merge_namespaced_prefixes_children_at_every_depth() {
+        let mut ctx = with_child("outer", "outer child");
+        ctx.merge_namespaced("inner", with_child("inner", "inner child"));
+
+        assert_eq!(ctx.tags, ["outer", "inner.inner"]);
+        let children: Vec<(&str, &[String], &[String])> = ctx
+            .children
+            .iter()
+            .map(|child| {
+                (
+                    child.source.as_str(),
+                    child.context.tags.as_slice(),
+                    child.context.skipped_children.as_slice(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            children,
+            [
+                (
+                    "parts.split",
+                    &["outer child".to_string()][..],
+                    &["parts.split: max depth 4 reached".to_string()][..]
+                ),
+                (
+                    "inner.parts.split",
+                    &["inner.inner child".to_string()][..],
+                    &["inner.parts.split: max depth 4 reached".to_string()][..]
+                ),
+            ]
+        );
+        assert_eq!(ctx.scanned_children, 2);
     }
 }
@@ -1 +1,3 @@
+pub mod config;
 pub mod data_objs;
+pub mod rule_mask;
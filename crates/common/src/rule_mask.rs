@@ -0,0 +1,145 @@
+/*
+
+Runtime on/off switch per signal and clinch clause. Bit positions come from the
+component's generated `<Component>Config::RULES`: signals as `family.name`, then
+clauses by label (rule ID, or `signal.family.name` for anonymous `when` clauses,
+with `#1`, `#2`, ... when a signal has several).
+
+*/
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleMask {
+    names: &'static [&'static str],
+    disabled: Vec<u64>,
+}
+
+impl RuleMask {
+    /// Everything enabled.
+    pub fn new(names: &'static [&'static str]) -> Self {
+        Self {
+            names,
+            disabled: vec![0; names.len().div_ceil(64)],
+        }
+    }
+
+    /// Disable every entry called `name`. Returns `false` if there is none.
+    pub fn disable(&mut self, name: &str) -> bool {
+        self.set(name, true)
+    }
+
+    /// Re-enable every entry called `name`. Returns `false` if there is none.
+    pub fn enable(&mut self, name: &str) -> bool {
+        self.set(name, false)
+    }
+
+    pub fn is_enabled(&self, bit: usize) -> bool {
+        self.disabled
+            .get(bit / 64)
+            .is_none_or(|word| word & (1 << (bit % 64)) == 0)
+    }
+
+    /// Names of disabled entries, once each, in bit order.
+    pub fn disabled_names(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = Vec::new();
+
+        for (bit, name) in self.names.iter().enumerate() {
+            if !self.is_enabled(bit) && !names.contains(name) {
+                names.push(name);
+            }
+        }
+
+        names
+    }
+
+    fn set(&mut self, name: &str, disabled: bool) -> bool {
+        let mut found = false;
+
+        for (bit, _) in self.names.iter().enumerate().filter(|(_, n)| **n == name) {
+            let mask = 1 << (bit % 64);
+            if disabled {
+                self.disabled[bit / 64] |= mask;
+            } else {
+                self.disabled[bit / 64] &= !mask;
+            }
+            found = true;
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &[&str] = &[
+        "format.pdf_magic",
+        "format.pe_magic",
+        "PDF-001",
+        "signal.format.pe_magic#1",
+        "signal.format.pe_magic#2",
+        "PDF-001",
+    ];
+
+    #[test]
+    fn everything_starts_enabled() {
+        let mask = RuleMask::new(RULES);
+
+        assert!((0..RULES.len()).all(|bit| mask.is_enabled(bit)));
+        assert!(mask.disabled_names().is_empty());
+    }
+
+    #[test]
+    fn disable_and_enable_by_name() {
+        let mut mask = RuleMask::new(RULES);
+
+        assert!(mask.disable("format.pe_magic"));
+        assert!(!mask.is_enabled(1));
+        assert!(mask.is_enabled(0));
+        assert_eq!(mask.disabled_names(), ["format.pe_magic"]);
+
+        assert!(mask.enable("format.pe_magic"));
+        assert!(mask.is_enabled(1));
+    }
+
+    #[test]
+    fn repeated_names_switch_together_and_are_listed_once() {
+        let mut mask = RuleMask::new(RULES);
+
+        mask.disable("PDF-001");
+
+        assert!(!mask.is_enabled(2));
+        assert!(!mask.is_enabled(5));
+        assert_eq!(mask.disabled_names(), ["PDF-001"]);
+    }
+
+    #[test]
+    fn anonymous_clauses_switch_on_their_own() {
+        let mut mask = RuleMask::new(RULES);
+
+        mask.disable("signal.format.pe_magic#2");
+
+        assert!(mask.is_enabled(3));
+        assert!(!mask.is_enabled(4));
+    }
+
+    #[test]
+    fn unknown_name_is_reported() {
+        let mut mask = RuleMask::new(RULES);
+
+        assert!(!mask.disable("PDF-999"));
+        assert_eq!(mask, RuleMask::new(RULES));
+    }
+
+    #[test]
+    fn bits_past_the_first_word() {
+        let names: Vec<&'static str> = (0..70).map(|i| &*format!("rule-{}", i).leak()).collect();
+        let mut mask = RuleMask::new(names.leak());
+
+        mask.disable("rule-65");
+
+        assert!(!mask.is_enabled(65));
+        assert!(mask.is_enabled(1));
+        assert_eq!(mask.disabled_names(), ["rule-65"]);
+    }
+}
@@ -31,6 +31,18 @@ lunch! {
         scope   = any
     }
 
+    /*
+
+    Tunables. They're usable by name in `when` conditions and `score` values, and
+    show up in the manifest with their defaults.
+
+     */
+
+    params {
+        HIGH_ENTROPY: f64 = 7.0,
+        PE_RISK: f64 = 3.0,
+    }
+
     operations {
 
         /*
@@ -64,13 +76,61 @@ lunch! {
             do entropy_probe   output entropy_probe
         }
 
+        /*
+
+        `children` marks an op that returns child Artifacts (anything you can iterate
+        as &Artifact). Each child gets run through this same pipeline before signals,
+        and its context ends up in ctx.children.
+
+         */
+
+        operation archive {
+            do zip_extract output members children
+        }
+
+    }
+
+    /*
+
+    Stages run after the top-level operations/signals, in order. `deep` only runs
+    its ops when PE magic fired, so the header parse costs nothing for PDFs.
+
+     */
+
+    stage deep when signal.format.pe_magic {
+        operations {
+            operation pe_probe {
+                do inspect_pe_header output pe_header
+            }
+        }
+
+        signals {
+            family pe {
+                signal no_pe_signature {
+                    derive from operation.pe_probe.inspect_pe_header
+                        when !pe_header.has_pe_signature
+                }
+            }
+        }
+    }
+
+    /*
+
+    Roll-ups fold child results into the parent after the children are scanned
+    and before clinch runs, so a zip holding a malicious PE is malicious too.
+
+     */
+
+    rollup {
+        risk: max(children)
+        tags: union prefixed "child:"
+        verdict: max(children)
     }
 
     signals {
 
         family format {
 
-
             /*
 
             Signals use basic rust syntax for logic. I kept "when" since I
@@ -107,22 +167,66 @@ lunch! {
 
             signal high_entropy {
                 derive from operation.magic.entropy_probe
-                    when entropy_probe.entropy > 7.0
+                    when entropy_probe.entropy > HIGH_ENTROPY
             }
 
         }
 
-        family risk {
+        family container {
 
-            signal suspicious_pe {
-                derive from operation.magic.inspect_magic
-                    when magic_probe.matched
-                      && magic_probe.magic[0] == 0x4D
-                      && magic_probe.magic[1] == 0x5A
+            signal zip_archive {
+                derive from operation.archive.zip_extract
+                    when !members.is_empty()
+            }
+
+        }
+
+        /*
+
+        cfg attributes work on families, signals, operations and clinch clauses, and
+        end up on the generated statements. A clause on a cfg'd signal needs the same
+        attribute or the macro rejects it.
+
+         */
+
+        #[cfg(feature = "experimental")]
+        family experimental {
+
+            signal low_entropy {
+                derive from operation.magic.entropy_probe
+                    when entropy_probe.entropy < 1.0
             }
 
         }
 
+        /*
+
+        Families can live in their own files. `include` splices the file in right
+        here, relative to this crate's Cargo.toml.
+
+         */
+
+        include "rules/families/risk.lunch"
+
+    }
+
+    suppress {
+
+        /*
+
+        Known false positives. A signal is suppressed right after it's computed, so
+        neither its clinch clauses nor a stage gated on it ever see it fire. Whatever
+        gets suppressed is listed in ctx.suppressed so it can be audited later.
+
+        Allowlists are read at compile time, relative to this crate's Cargo.toml.
+
+         */
+
+        signal.format.pe_magic
+            when artifact.meta["signer"] == "Contoso"
+
+        tag type = "pe" for hash in "allowlist.txt"
+
     }
 
     clinch {
@@ -141,7 +245,16 @@ lunch! {
 
          */
 
+        /*
+
+        rule gives a clause a stable ID so every action it takes can be traced back
+        to it (ctx.attributions) and so it shows up in the manifest. Plain `when`
+        clauses still work, they're just anonymous.
+
+         */
+
         // --- PDF handling ---
+        rule PDF-001 "PDF magic header" severity low
         when signal.format.pdf_magic {
             tag += "type:pdf"
             emit Emit::PdfMagic
@@ -150,32 +263,97 @@ lunch! {
         }
 
         when signal.format.classified_pdf {
-            tag += "classified:pdf"
+            tag += "classified:{format_probe.format}"
+            tag format = format_probe.format
             score confidence += 2.0
         }
 
+        /*
+
+        verdicts only ever go up. malicious > suspicious > clean, so the
+        order clauses raise them in doesn't matter.
+
+        halt stops every clause after the one it's in. Order does matter for that one.
+
+         */
+
+        // --- Containers ---
+        when signal.container.zip_archive {
+            tag += "type:zip"
+            score trust = -0.5
+        }
+
+        #[cfg(feature = "experimental")]
+        when signal.experimental.low_entropy {
+            tag += "experimental:low_entropy"
+        }
+
+        // --- Deep stage ---
+        when signal.pe.no_pe_signature {
+            tag += "pe:no_signature@{pe_header.pe_offset}"
+        }
+
         // --- PE handling ---
+        rule PE-001 "PE magic header" severity medium
         when signal.format.pe_magic {
-            tag += "type:pe"
+            tag type = "pe"
             emit Emit::PortableExecutable
             run deferred PeStaticAnalyzer
-            score risk += 3.0
+            run pipeline crate::pe_deep_mint::pe_deep
+            score risk += PE_RISK
+            verdict suspicious
         }
 
         // --- Structural anomaly ---
+        rule ENT-001 "High entropy content" severity medium
         when signal.structural.high_entropy {
             tag += "anomaly:high_entropy"
-            emit Emit::HighEntropy
+            emit Emit::HighEntropy { entropy = entropy_probe.entropy }
             score risk += 5.0
         }
 
         // --- Risk escalation ---
+        rule RISK-001 "Suspicious PE" severity high
         when signal.risk.suspicious_pe {
             tag += "risk:suspicious_pe"
             score threat = 9.0
+            verdict malicious
+            halt
         }
 
     }
 
+    /*
+
+    Profiles swap score values per deployment without copying rules. Each one gets
+    its own entry fn (run_lstran_pipeline_email), or set `profile = "email"` in the
+    config file.
+
+     */
+
+    profile email {
+        score risk for signal.format.pe_magic = 6.0
+    }
+
+    }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suppressed_gate_signal_keeps_its_stage_closed() {
+        let artifact = Artifact {
+            bytes: b"MZ\x90\x00".to_vec(),
+            text: None,
+            meta: [(String::from("signer"), String::from("Contoso"))].into(),
+        };
+
+        let ctx = run_lstran_pipeline(&artifact);
+
+        assert_eq!(ctx.suppressed, ["signal.format.pe_magic"]);
+        assert!(ctx.skipped_stages.iter().any(|stage| stage == "deep"));
+        assert!(ctx.children.is_empty());
+    }
 }
@@ -4,4 +4,6 @@ Necessary. This is how the macro registers the operations in the clinch pipeline
 
 */
 
-pub use crate::ops_file::{classify_format, entropy_probe, inspect_magic};
+pub use crate::ops_file::{
+    classify_format, entropy_probe, inspect_magic, inspect_pe_header, zip_extract,
+};
@@ -3,14 +3,89 @@ use common::data_objs::Artifact;
 pub mod lstran_mint;
 pub mod lstran_ops;
 pub mod ops_file;
+pub mod pe_deep_mint;
+pub mod pe_deep_ops;
 
 fn main() {
+    let config = match config_path() {
+        Some(path) => match lstran_mint::LstranConfig::load(&path) {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!("{}: {}", path, err);
+                std::process::exit(2);
+            }
+        },
+        None => lstran_mint::LstranConfig::default(),
+    };
+    println!("Config: {:?}", config);
+
     let artifact = Artifact {
         bytes: vec![0x25, 0x50, 0x44, 0x46],
         text: Some(String::from("Hello, world!")),
         meta: std::collections::HashMap::new(),
     };
-    let ctx = lstran_mint::run_lstran_pipeline(&artifact);
+    let ctx = lstran_mint::run_lstran_pipeline_with(&artifact, &config);
 
     println!("Pipeline context: {:?}", ctx);
+    println!("Manifest: {}", lstran_mint::lstran_manifest());
+
+    // Two stored zip members: a PDF and a PE stub.
+    let mut zip = Vec::new();
+    for (name, data) in [("doc.pdf", &b"%PDF"[..]), ("tool.exe", &b"MZ\x90\x00"[..])] {
+        zip.extend_from_slice(&[0x50, 0x4B, 0x03, 0x04, 20, 0, 0, 0, 0, 0]);
+        zip.extend_from_slice(&[0; 8]);
+        zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&[0, 0]);
+        zip.extend_from_slice(name.as_bytes());
+        zip.extend_from_slice(data);
+    }
+    let archive = Artifact {
+        bytes: zip,
+        text: None,
+        meta: std::collections::HashMap::new(),
+    };
+    let archive_ctx = lstran_mint::run_lstran_pipeline_with(&archive, &config);
+    println!(
+        "Archive: verdict {:?}, risk {}, tags {:?}",
+        archive_ctx.verdict,
+        archive_ctx.score("risk"),
+        archive_ctx.tags
+    );
+    for child in &archive_ctx.children {
+        println!(
+            "Child {} from {}: tags {:?}",
+            child.name.as_deref().unwrap_or("?"),
+            child.source,
+            child.context.tags
+        );
+    }
+
+    let pe = Artifact {
+        bytes: b"MZ\x90\x00".to_vec(),
+        text: None,
+        meta: std::collections::HashMap::new(),
+    };
+    println!(
+        "PE risk: default {}, email profile {}",
+        lstran_mint::run_lstran_pipeline(&pe).score("risk"),
+        lstran_mint::run_lstran_pipeline_email(&pe).score("risk")
+    );
+
+    let explained = lstran_mint::run_lstran_pipeline_explained(&artifact);
+    println!("Explain:\n{}", explained.explain());
+}
+
+/// `--config <file>`: TOML, or JSON when the file ends in `.json`.
+fn config_path() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next();
+        }
+    }
+
+    None
 }
@@ -11,7 +11,8 @@ Ideally don't do things like spin up threads in this. It also needs to be sync.
 
 */
 
-use common::data_objs::{Artifact, LuciusContext};
+use common::data_objs::Artifact;
+use std::collections::HashMap;
 
 pub struct InspectMagicResult {
     pub matched: bool,
@@ -61,3 +62,84 @@ pub fn classify_format(artifact: &Artifact) -> FormatClassResult {
 
     FormatClassResult { format }
 }
+
+pub struct PeHeaderResult {
+    /// Offset of the PE header, read from `e_lfanew` in the DOS header.
+    pub pe_offset: usize,
+    /// `PE\0\0` found at `pe_offset`.
+    pub has_pe_signature: bool,
+}
+
+pub fn inspect_pe_header(artifact: &Artifact) -> PeHeaderResult {
+    let bytes = &artifact.bytes;
+
+    let pe_offset = bytes
+        .get(0x3C..0x40)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        .unwrap_or(0);
+
+    let has_pe_signature = pe_offset != 0
+        && bytes.get(pe_offset..pe_offset + 4) == Some(&[0x50, 0x45, 0x00, 0x00][..]);
+
+    PeHeaderResult {
+        pe_offset,
+        has_pe_signature,
+    }
+}
+
+/// Stored (uncompressed) zip members as child artifacts. Anything compressed or
+/// streamed with a data descriptor ends the walk; this is a demo, not a zip reader.
+pub fn zip_extract(artifact: &Artifact) -> Vec<Artifact> {
+    let bytes = &artifact.bytes;
+    let mut members = Vec::new();
+    let mut offset = 0;
+
+    let u16_at = |at: usize| {
+        bytes
+            .get(at..at + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]) as usize)
+    };
+    let u32_at = |at: usize| {
+        bytes
+            .get(at..at + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+    };
+
+    while bytes.get(offset..offset + 4) == Some(&[0x50, 0x4B, 0x03, 0x04][..]) {
+        let (Some(flags), Some(method), Some(size), Some(name_len), Some(extra_len)) = (
+            u16_at(offset + 6),
+            u16_at(offset + 8),
+            u32_at(offset + 18),
+            u16_at(offset + 26),
+            u16_at(offset + 28),
+        ) else {
+            break;
+        };
+
+        if method != 0 || flags & 0x08 != 0 {
+            break;
+        }
+
+        let name_start = offset + 30;
+        let data_start = name_start + name_len + extra_len;
+        let (Some(name), Some(data)) = (
+            bytes.get(name_start..name_start + name_len),
+            bytes.get(data_start..data_start + size),
+        ) else {
+            break;
+        };
+
+        members.push(Artifact {
+            bytes: data.to_vec(),
+            text: None,
+            meta: HashMap::from([(
+                "name".to_string(),
+                String::from_utf8_lossy(name).into_owned(),
+            )]),
+        });
+
+        offset = data_start + size;
+    }
+
+    members
+}
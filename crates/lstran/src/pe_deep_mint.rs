@@ -0,0 +1,54 @@
+/*
+
+Deeper PE analysis. lstran reaches this through `run pipeline crate::pe_deep_mint::pe_deep`
+when PE magic fires, so everything here lands in lstran's context under `pe_deep.`.
+
+*/
+
+use common::data_objs::{Artifact, LuciusContext};
+
+use lucius_macro::lunch;
+
+lunch! {
+
+    component = pe_deep
+
+    {
+
+    operations {
+        operation header {
+            do inspect_pe_header output pe_header
+        }
+    }
+
+    signals {
+        family pe {
+            signal valid_header {
+                derive from operation.header.inspect_pe_header
+                    when pe_header.has_pe_signature
+            }
+
+            signal truncated {
+                derive from operation.header.inspect_pe_header
+                    when !pe_header.has_pe_signature
+            }
+        }
+    }
+
+    clinch {
+        rule PE-DEEP-001 "Valid PE header" severity low
+        when signal.pe.valid_header {
+            tag offset = pe_header.pe_offset
+            score risk += 1.0
+        }
+
+        rule PE-DEEP-002 "MZ without a PE header" severity medium
+        when signal.pe.truncated {
+            tag += "pe:truncated"
+            score risk += 2.0
+            verdict suspicious
+        }
+    }
+
+    }
+}
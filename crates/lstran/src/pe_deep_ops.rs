@@ -0,0 +1,7 @@
+/*
+
+Ops for the pe_deep component. Same deal as lstran_ops.rs.
+
+*/
+
+pub use crate::ops_file::inspect_pe_header;
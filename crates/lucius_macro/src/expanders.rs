@@ -1,16 +1,58 @@
-use crate::{LunchGenInput, PipelineIndex};
+use lunch_core::{
+    LunchGenInput, PipelineIndex,
+    include::Loader,
+    lints::{Warning, lint_pipeline},
+};
 use proc_macro2::TokenStream as TokenStream2;
+use syn::Error;
 
-pub fn lunch_expander(input: TokenStream2) -> syn::Result<(LunchGenInput, PipelineIndex)> {
-    let parsed = match syn::parse2::<LunchGenInput>(input) {
-        Ok(v) => v,
-        Err(e) => return Err(e),
-    };
+pub struct Expansion {
+    pub parsed: LunchGenInput,
+    pub index: PipelineIndex,
+    pub warnings: Vec<Warning>,
+    /// Rule files read through `file = ".."` or `include ".."`.
+    pub files: Vec<String>,
+}
+
+pub fn lunch_expander(input: TokenStream2) -> syn::Result<Expansion> {
+    let mut loader = Loader::new();
+    // Errors resolving a file already sit on the literal that names it.
+    let input = loader.resolve(input)?;
 
-    let index = match parsed.pipeline.validate_and_index() {
-        Ok(i) => i,
-        Err(e) => return Err(e),
-    };
+    let (parsed, index, mut warnings) = front_end(input).map_err(|err| in_files(&loader, err))?;
+    for warning in &mut warnings {
+        if let Some(path) = loader.origin(warning.span) {
+            warning.message = format!("{}: {}", path, warning.message);
+        }
+    }
+
+    Ok(Expansion {
+        parsed,
+        index,
+        warnings,
+        files: loader.files,
+    })
+}
+
+fn front_end(input: TokenStream2) -> syn::Result<(LunchGenInput, PipelineIndex, Vec<Warning>)> {
+    let parsed = syn::parse2::<LunchGenInput>(input)?;
+    let index = parsed.pipeline.validate_and_index(&parsed.component)?;
+    let warnings = lint_pipeline(&parsed.pipeline, &index);
+
+    Ok((parsed, index, warnings))
+}
 
-    Ok((parsed, index))
+/// Prefix each error on a token read from a file with that file's path. The
+/// error keeps the span of the literal that read it.
+fn in_files(loader: &Loader, err: Error) -> Error {
+    err.into_iter()
+        .map(|e| match loader.origin(e.span()) {
+            Some(path) => Error::new(e.span(), format!("{}: {}", path, e)),
+            None => e,
+        })
+        .reduce(|mut all, e| {
+            all.combine(e);
+            all
+        })
+        .expect("a syn::Error holds at least one message")
 }
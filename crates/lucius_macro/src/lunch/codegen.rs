@@ -1,17 +1,23 @@
 use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
 use syn::Ident;
 
-use crate::lunch::{
-    index::pipeline::PipelineIndex, lowering::pipeline::lower_pipeline,
-    parse::pipeline::PipelineAst,
-};
+use crate::lunch::lowering::{files::lower_files, lints::lower_lints, pipeline::lower_pipeline};
+use lunch_core::{index::pipeline::PipelineIndex, lints::Warning};
 
 pub fn generate(
-    _ast: &PipelineAst,
     index: &PipelineIndex,
     component: Ident,
+    warnings: &[Warning],
+    files: &[String],
 ) -> syn::Result<TokenStream2> {
     let lowered = lower_pipeline(index, &component.to_string());
+    let lints = lower_lints(warnings);
+    let files = lower_files(files);
 
-    Ok(lowered)
+    Ok(quote! {
+        #lowered
+        #lints
+        #files
+    })
 }
@@ -0,0 +1,55 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+use lunch_core::index::operations::OperationIndex;
+
+/// Child artifacts from `children` steps are scanned with this same component
+/// and config, one level down, before signals run. Depth, count and total size are bounded
+/// by `ctx.limits`; counts and sizes are accounted across the whole tree.
+pub fn lower_children(ops: &OperationIndex, component: &str, stage: Option<&str>) -> TokenStream2 {
+    let run_fn = format_ident!("run_{}_pipeline_in_with", component);
+
+    let mut steps: Vec<(&String, &String, bool, &Vec<syn::Attribute>)> = ops
+        .index
+        .iter()
+        .filter(|(_, op)| op.stage.as_deref() == stage)
+        .flat_map(|(op_name, op)| {
+            op.steps
+                .iter()
+                .filter(|(_, step)| step.children)
+                .map(move |(step_name, _)| (op_name, step_name, op.gated, &op.cfg))
+        })
+        .collect();
+    steps.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+    let scans = steps.into_iter().map(|(op_name, step_name, gated, cfg)| {
+        let result_ident = format_ident!("__op_{}_step_{}", op_name, step_name);
+        let source = format!("{}.{}", op_name, step_name);
+
+        // a gated step that never ran has no children
+        let children = if gated {
+            quote! { #result_ident.iter().flatten() }
+        } else {
+            quote! { (&#result_ident).into_iter() }
+        };
+
+        quote! {
+            #(#cfg)*
+            for child in #children {
+                let child: &Artifact = child;
+
+                if let Some(reason) = ctx.child_limit_reached(child) {
+                    ctx.skipped_children.push(format!("{}: {}", #source, reason));
+                    continue;
+                }
+
+                let child_ctx = #run_fn(child, ctx.child_scan(child), __lunch_config);
+                ctx.attach_child(#source, child_ctx, child);
+            }
+        }
+    });
+
+    quote! {
+        #(#scans)*
+    }
+}
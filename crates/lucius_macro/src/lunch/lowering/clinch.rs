@@ -1,16 +1,29 @@
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
 
-use crate::lunch::index::clinch::{ClinchAction, ClinchIndex, ScoreOp};
+use crate::lunch::lowering::{
+    profiles::lower_score_value, rule_mask::clause_bit, signals::with_step_binding,
+};
+use lunch_core::index::{
+    clinch::{ClinchAction, ClinchClauseInfo, ClinchIndex, ScoreOp, TagValue, VerdictLevel},
+    operations::OperationIndex,
+    profiles::ProfileIndex,
+    signals::SignalIndex,
+};
 
-pub fn lower_clinch(index: &ClinchIndex) -> TokenStream2 {
+pub fn lower_clinch(
+    index: &ClinchIndex,
+    signals: &SignalIndex,
+    ops: &OperationIndex,
+    profiles: &ProfileIndex,
+    component: &str,
+) -> TokenStream2 {
     let mut blocks = Vec::new();
 
-    for (signal_id, actions) in &index.by_signal {
+    for (position, clause) in index.clauses.iter().enumerate() {
+        let bit = clause_bit(signals, position);
         blocks.push(lower_clinch_clause(
-            &signal_id.family,
-            &signal_id.name,
-            actions,
+            clause, bit, signals, ops, profiles, component,
         ));
     }
 
@@ -19,30 +32,120 @@ pub fn lower_clinch(index: &ClinchIndex) -> TokenStream2 {
     }
 }
 
-fn lower_clinch_clause(family: &str, name: &str, actions: &[ClinchAction]) -> TokenStream2 {
-    let sig_ident = format_ident!("__signal_{}_{}", family, name);
+fn lower_clinch_clause(
+    clause: &ClinchClauseInfo,
+    bit: usize,
+    signals: &SignalIndex,
+    ops: &OperationIndex,
+    profiles: &ProfileIndex,
+    component: &str,
+) -> TokenStream2 {
+    let sig_ident = format_ident!("__signal_{}_{}", clause.signal.family, clause.signal.name);
+    let label = clause.label();
 
-    let lowered_actions: Vec<TokenStream2> = actions.iter().map(lower_action).collect();
+    // provenance inputs for explain mode
+    let signal = format!("{}.{}", clause.signal.family, clause.signal.name);
+    let derived = signals
+        .families
+        .get(&clause.signal.family)
+        .and_then(|family| family.signals.get(&clause.signal.name));
+    let step = derived
+        .map(|sig| format!("{}.{}", sig.derives_from.operation, sig.derives_from.step))
+        .unwrap_or_default();
+
+    let lowered_actions: Vec<TokenStream2> = clause
+        .actions
+        .iter()
+        .map(|action| {
+            let lowered = lower_action(action, clause, profiles, component);
+            // a tag is described by the tag it pushed, so the trail matches `ctx.tags`
+            let description = match action {
+                ClinchAction::Tag { .. } => quote! { __tag.as_str() },
+                _ => {
+                    let description = action.to_string();
+                    quote! { #description }
+                }
+            };
+
+            let record = match action {
+                ClinchAction::Score { key, .. } => quote! {
+                    if let Some(__before) = __before {
+                        ctx.record(#label, &[#signal], #step, #description, Some((#key, __before)));
+                    }
+                },
+                _ => quote! {
+                    ctx.record(#label, &[#signal], #step, #description, None);
+                },
+            };
+
+            // the score before the action is only read when explain mode is on
+            let capture = match action {
+                ClinchAction::Score { key, .. } => {
+                    quote! { let __before = ctx.explain_mode.then(|| ctx.score(#key)); }
+                }
+                _ => quote! {},
+            };
+
+            quote! {
+                #capture
+                #lowered
+                ctx.attribute(#label, #description);
+                #record
+            }
+        })
+        .collect();
+
+    let body = quote! { #(#lowered_actions)* };
+    let body = match derived {
+        Some(sig) => with_step_binding(&sig.derives_from, ops, body, quote! { {} }),
+        None => body,
+    };
+
+    let cfg = &clause.cfg;
 
     quote! {
-        if #sig_ident {
-            #(#lowered_actions)*
+        #(#cfg)*
+        if !ctx.halted && __lunch_config.rule_mask.is_enabled(#bit) && #sig_ident {
+            #body
         }
     }
 }
 
-fn lower_action(action: &ClinchAction) -> TokenStream2 {
+fn lower_action(
+    action: &ClinchAction,
+    clause: &ClinchClauseInfo,
+    profiles: &ProfileIndex,
+    component: &str,
+) -> TokenStream2 {
     match action {
+        // `tag += <value>` keeps its original output, `tag:` and the quoted
+        // value (`tag:"type:pdf"`). `tag <key> = <value>` pushes `<key>:<value>`.
+        // The tag stays in `__tag` for the attribution and provenance records.
         ClinchAction::Tag { key, value } => {
-            let tag = format!("{}:{}", key, value);
+            let tag = lower_tag(key.as_deref(), value);
             quote! {
-                ctx.tags.push(#tag.to_string());
+                let __tag: String = #tag;
+                ctx.push_tag(__tag.clone());
             }
         }
 
-        ClinchAction::Emit { event } => {
+        ClinchAction::Emit { event, payload } => {
+            if payload.is_empty() {
+                return quote! {
+                    ctx.emits.push(#event.to_string());
+                };
+            }
+
+            let fields = payload.iter().map(|(name, value)| {
+                quote! { (#name.to_string(), (#value).to_string()) }
+            });
+
             quote! {
                 ctx.emits.push(#event.to_string());
+                ctx.emit_payloads.push(::common::data_objs::EmitPayload {
+                    event: #event.to_string(),
+                    fields: vec![#(#fields),*],
+                });
             }
         }
 
@@ -52,12 +155,45 @@ fn lower_action(action: &ClinchAction) -> TokenStream2 {
             }
         }
 
+        ClinchAction::RunPipeline { component, module } => {
+            let run_fn = format_ident!("run_{}_pipeline_nested", component);
+            let run_fn = match module {
+                Some(module) => quote! { #module::#run_fn },
+                None => quote! { #run_fn },
+            };
+            quote! {
+                if ctx.depth < ::common::data_objs::MAX_PIPELINE_DEPTH {
+                    let nested = #run_fn(artifact, ctx.nested(), &__lunch_config.changed_overrides());
+                    ctx.merge_namespaced(#component, nested);
+                } else {
+                    ctx.skipped_pipelines.push(#component.to_string());
+                }
+            }
+        }
+
+        ClinchAction::Verdict { level } => {
+            let variant = match level {
+                VerdictLevel::Clean => quote! { Clean },
+                VerdictLevel::Suspicious => quote! { Suspicious },
+                VerdictLevel::Malicious => quote! { Malicious },
+            };
+            quote! {
+                ctx.raise_verdict(::common::data_objs::Verdict::#variant);
+            }
+        }
+
+        ClinchAction::Halt => {
+            quote! {
+                ctx.halted = true;
+            }
+        }
+
         ClinchAction::Score {
             key,
             operator,
             value,
         } => {
-            let v = *value;
+            let v = lower_score_value(profiles, component, &clause.signal, key, value);
             match operator {
                 ScoreOp::Add => quote! {
                     *ctx.scores.entry(#key.to_string()).or_insert(0.0) += #v;
@@ -75,3 +211,29 @@ fn lower_action(action: &ClinchAction) -> TokenStream2 {
         }
     }
 }
+
+/// The tag a `tag` action pushes, as a `String` expression.
+fn lower_tag(key: Option<&str>, value: &TagValue) -> TokenStream2 {
+    match (key, value) {
+        (None, TagValue::Literal(value)) => {
+            let tag = format!("tag:{:?}", value);
+            quote! { #tag.to_string() }
+        }
+        (None, TagValue::Interpolated { template, args }) => quote! {
+            format!("tag:{:?}", format!(#template, #((#args)),*))
+        },
+        (None, TagValue::Binding(_)) => unreachable!("a binding tag value always has a key"),
+        (Some(key), TagValue::Literal(value)) => {
+            let tag = format!("{}:{}", key, value);
+            quote! { #tag.to_string() }
+        }
+        (Some(key), TagValue::Interpolated { template, args }) => {
+            let template = format!("{}:{}", key, template);
+            quote! { format!(#template, #((#args)),*) }
+        }
+        (Some(key), TagValue::Binding(path)) => {
+            let template = format!("{}:{{}}", key);
+            quote! { format!(#template, #path) }
+        }
+    }
+}
@@ -0,0 +1,22 @@
+/*
+
+Rebuild tracking for rule files read through `file = ".."` and `include ".."`.
+Cargo doesn't see a proc macro's own reads, but it does see `include_bytes!`,
+so each file is included into an unused const.
+
+*/
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+pub fn lower_files(files: &[String]) -> TokenStream2 {
+    files
+        .iter()
+        .map(|file| {
+            let path = format!("/{}", file);
+            quote! {
+                const _: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), #path));
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,36 @@
+/*
+
+Lint findings as compiler warnings. Stable proc macros can't emit warnings
+directly, so each finding lowers to a `#[deprecated]` unit struct that is used
+right away, spanned at the DSL item. rustc then reports the deprecation note at
+that span. The structs live in anonymous consts and generate no code.
+
+*/
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote_spanned};
+
+use lunch_core::lints::Warning;
+
+pub fn lower_lints(warnings: &[Warning]) -> TokenStream2 {
+    warnings
+        .iter()
+        .map(|warning| {
+            let lint = format_ident!("{}", warning.lint.as_str(), span = warning.span);
+            let note = format!(
+                "{} (`#[allow(lunch::{})]` to silence)",
+                warning.message,
+                warning.lint.as_str()
+            );
+
+            quote_spanned! { warning.span =>
+                const _: () = {
+                    #[deprecated(note = #note)]
+                    #[allow(non_camel_case_types)]
+                    struct #lint;
+                    let _ = #lint;
+                };
+            }
+        })
+        .collect()
+}
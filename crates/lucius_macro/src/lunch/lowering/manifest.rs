@@ -1,7 +1,23 @@
 /*
 
+Provenance manifest built from the indexed pipeline. Lowered as a JSON string so
+consumers can see what a compiled component contains without expanding the macro.
 
-Placeholder file. This will be a provenance manifest built from the indexed pipeline.
+*/
 
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
 
-*/
+use lunch_core::{index::pipeline::PipelineIndex, manifest::build_manifest};
+
+pub fn lower_manifest(index: &PipelineIndex, component: &str) -> TokenStream2 {
+    let fn_ident = format_ident!("{}_manifest", component);
+    let manifest = build_manifest(index, component).to_string();
+
+    quote! {
+        /// JSON provenance manifest for this component.
+        pub fn #fn_ident() -> &'static str {
+            #manifest
+        }
+    }
+}
@@ -1,5 +1,14 @@
+pub mod children;
 pub mod clinch;
+pub mod files;
+pub mod lints;
 pub mod manifest;
 pub mod operations;
+pub mod params;
 pub mod pipeline;
+pub mod profiles;
+pub mod rollup;
+pub mod rule_mask;
 pub mod signals;
+pub mod stages;
+pub mod suppress;
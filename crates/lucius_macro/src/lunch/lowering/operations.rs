@@ -1,15 +1,22 @@
-use crate::lunch::index::{
+use lunch_core::index::{
     common::StepInfo,
     operations::{OperationIndex, OperationInfo},
 };
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
 
-pub fn lower_operations(ops: &OperationIndex, ops_path: &syn::Path) -> TokenStream2 {
+/// Ops declared in `stage` (`None` for top-level `operations`).
+pub fn lower_operations(
+    ops: &OperationIndex,
+    ops_path: &syn::Path,
+    stage: Option<&str>,
+) -> TokenStream2 {
     let mut lowered = Vec::new();
 
     for (op_name, op_info) in &ops.index {
-        lowered.push(lower_operation(op_name, op_info, ops_path));
+        if op_info.stage.as_deref() == stage {
+            lowered.push(lower_operation(op_name, op_info, ops_path));
+        }
     }
 
     quote! { #(#lowered)* }
@@ -19,9 +26,19 @@ fn lower_operation(op_name: &str, op_info: &OperationInfo, ops_path: &syn::Path)
     let mut step_calls = Vec::new();
 
     for (step_name, step_info) in &op_info.steps {
-        step_calls.push(crate::lunch::lowering::operations::lower_step(
-            op_name, step_name, step_info, ops_path,
-        ));
+        let call =
+            crate::lunch::lowering::operations::lower_step(op_name, step_name, step_info, ops_path);
+
+        let call = match &op_info.stage {
+            Some(stage) if op_info.gated => gate_step(op_name, step_name, stage, call),
+            _ => call,
+        };
+
+        let cfg = &op_info.cfg;
+        step_calls.push(quote! {
+            #(#cfg)*
+            #call
+        });
     }
 
     quote! {
@@ -29,6 +46,21 @@ fn lower_operation(op_name: &str, op_info: &OperationInfo, ops_path: &syn::Path)
     }
 }
 
+/// Gated stages hold `Some(result)` only when the gate fired.
+fn gate_step(op_name: &str, step_name: &str, stage: &str, call: TokenStream2) -> TokenStream2 {
+    let result_ident = format_ident!("__op_{}_step_{}", op_name, step_name);
+    let stage_ident = format_ident!("__stage_{}", stage);
+
+    quote! {
+        let #result_ident = if #stage_ident {
+            #call
+            Some(#result_ident)
+        } else {
+            None
+        };
+    }
+}
+
 pub fn lower_step(
     op_name: &str,
     step_name: &str,
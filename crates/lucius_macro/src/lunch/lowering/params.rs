@@ -0,0 +1,240 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+use crate::lunch::lowering::profiles::profile_ident;
+use lunch_core::index::params::{ParamIndex, ParamInfo, ParamType, ParamValue};
+
+/// `params` lower to consts in `<component>_params`.
+pub fn lower_params_consts(index: &ParamIndex, component: &str) -> TokenStream2 {
+    let mod_ident = format_ident!("{}_params", component);
+    let consts = index.params.iter().map(|param| {
+        let name = format_ident!("{}", param.name);
+        let ty = format_ident!("{}", param.ty.as_str());
+        let value = lower_value(param);
+
+        quote! { pub const #name: #ty = #value; }
+    });
+
+    quote! {
+        /// Defaults declared in this component's `params` block.
+        pub mod #mod_ident {
+            #(#consts)*
+        }
+    }
+}
+
+/// Inside the run fn each param is a local read from `__lunch_config`, so `when` and
+/// `score` expressions read a plain name. A const in scope would turn that
+/// `let` into a pattern.
+pub fn lower_params_locals(index: &ParamIndex) -> TokenStream2 {
+    let locals = index.params.iter().map(|param| {
+        let name = format_ident!("{}", param.name);
+        let ty = format_ident!("{}", param.ty.as_str());
+
+        quote! {
+            #[allow(unused_variables)]
+            let #name: #ty = __lunch_config.#name;
+        }
+    });
+
+    quote! {
+        #(#locals)*
+    }
+}
+
+/// `<Component>Config`: one field per param, defaulting to the declared value,
+/// plus the rule mask and the score profile. Overrides can only name declared params and existing
+/// signals/rules; rule logic is not configurable.
+pub fn lower_config(index: &ParamIndex, component: &str, rules: &[String]) -> TokenStream2 {
+    let config_ident = config_ident(component);
+    let profile_ident = profile_ident(component);
+    let mod_ident = format_ident!("{}_params", component);
+
+    let fields = index.params.iter().map(|param| {
+        let name = format_ident!("{}", param.name);
+        let ty = format_ident!("{}", param.ty.as_str());
+        quote! { pub #name: #ty }
+    });
+
+    let defaults = index.params.iter().map(|param| {
+        let name = format_ident!("{}", param.name);
+        quote! { #name: #mod_ident::#name }
+    });
+
+    let overrides = index.params.iter().map(|param| {
+        let name = format_ident!("{}", param.name);
+        let key = &param.name;
+        let getter = format_ident!("as_{}", param.ty.as_str());
+        quote! { #key => config.#name = value.#getter(key)?, }
+    });
+
+    let changed = index.params.iter().map(|param| {
+        let name = format_ident!("{}", param.name);
+        let key = &param.name;
+        let value = match param.ty {
+            ParamType::F64 => quote! { Float(self.#name) },
+            ParamType::I64 => quote! { Int(self.#name) },
+            ParamType::U64 | ParamType::Usize => quote! { Int(self.#name as i64) },
+            ParamType::Bool => quote! { Bool(self.#name) },
+        };
+        quote! {
+            if self.#name != defaults.#name {
+                overrides
+                    .values
+                    .insert(#key.to_string(), ::common::config::ParamValue::#value);
+            }
+        }
+    });
+
+    let shared = index.params.iter().map(|param| {
+        let name = format_ident!("{}", param.name);
+        let key = &param.name;
+        let getter = format_ident!("as_{}", param.ty.as_str());
+        quote! {
+            if let Some(Ok(value)) = overrides.values.get(#key).map(|value| value.#getter(#key)) {
+                config.#name = value;
+            }
+        }
+    });
+
+    quote! {
+        #[allow(non_snake_case)]
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct #config_ident {
+            #(#fields,)*
+            /// Signals and clinch clauses switched off for this run.
+            pub rule_mask: ::common::rule_mask::RuleMask,
+            /// Score overlay; see the `profile` blocks.
+            pub profile: #profile_ident,
+        }
+
+        impl Default for #config_ident {
+            fn default() -> Self {
+                Self {
+                    #(#defaults,)*
+                    rule_mask: ::common::rule_mask::RuleMask::new(Self::RULES),
+                    profile: #profile_ident::Default,
+                }
+            }
+        }
+
+        impl #config_ident {
+            /// `RuleMask` bit names: signals as `family.name`, then clinch clauses by label.
+            pub const RULES: &'static [&'static str] = &[#(#rules),*];
+
+            /// Defaults with `overrides` applied. Keys that aren't params of this component are rejected.
+            pub fn from_overrides(
+                overrides: &::common::config::ParamOverrides,
+            ) -> Result<Self, ::common::config::ConfigError> {
+                let mut config = Self::default();
+
+                // `value` is unused when there are no params
+                #[allow(unused_variables)]
+                for (key, value) in &overrides.values {
+                    match key.as_str() {
+                        #(#overrides)*
+                        _ => return Err(::common::config::ConfigError::UnknownParam(key.clone())),
+                    }
+                }
+
+                for name in &overrides.disabled {
+                    if !config.rule_mask.disable(name) {
+                        return Err(::common::config::ConfigError::UnknownRule(name.clone()));
+                    }
+                }
+
+                if let Some(name) = &overrides.profile {
+                    config.profile = #profile_ident::from_name(name)
+                        .ok_or_else(|| ::common::config::ConfigError::UnknownProfile(name.clone()))?;
+                }
+
+                Ok(config)
+            }
+
+            /// Defaults with the parts of `overrides` this component shares by name:
+            /// params of the same type, signals and rules, and the profile. The rest
+            /// is skipped, so a caller's overrides can't fail a nested run.
+            pub fn from_shared_overrides(overrides: &::common::config::ParamOverrides) -> Self {
+                let mut config = Self::default();
+
+                #(#shared)*
+
+                for name in &overrides.disabled {
+                    config.rule_mask.disable(name);
+                }
+
+                if let Some(profile) = overrides.profile.as_deref().and_then(#profile_ident::from_name) {
+                    config.profile = profile;
+                }
+
+                config
+            }
+
+            /// Where this config differs from the defaults, as overrides. `run pipeline`
+            /// hands them to the nested component.
+            pub fn changed_overrides(&self) -> ::common::config::ParamOverrides {
+                // unused when there are no params
+                #[allow(unused_variables)]
+                let defaults = Self::default();
+                let mut overrides = ::common::config::ParamOverrides::default();
+
+                #(#changed)*
+
+                overrides.disabled = self
+                    .rule_mask
+                    .disabled_names()
+                    .into_iter()
+                    .map(String::from)
+                    .collect();
+                if self.profile != #profile_ident::Default {
+                    overrides.profile = Some(self.profile.as_str().to_string());
+                }
+
+                overrides
+            }
+
+            /// Load overrides from a TOML file, or JSON when the extension is `.json`.
+            pub fn load(path: impl AsRef<::std::path::Path>) -> Result<Self, ::common::config::ConfigError> {
+                Self::from_overrides(&::common::config::ParamOverrides::load(path)?)
+            }
+        }
+    }
+}
+
+/// `pe_deep` -> `PeDeepConfig`
+pub fn config_ident(component: &str) -> syn::Ident {
+    format_ident!("{}Config", camel_case(component))
+}
+
+/// `pe_deep` -> `PeDeep`
+pub fn camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn lower_value(param: &ParamInfo) -> TokenStream2 {
+    match param.value {
+        ParamValue::Float(v) if v < 0.0 => {
+            let v = -v;
+            quote! { -#v }
+        }
+        ParamValue::Float(v) => quote! { #v },
+        ParamValue::Int(v) => {
+            let abs = proc_macro2::Literal::u128_unsuffixed(v.unsigned_abs());
+            if v < 0 {
+                quote! { -#abs }
+            } else {
+                quote! { #abs }
+            }
+        }
+        ParamValue::Bool(v) => quote! { #v },
+    }
+}
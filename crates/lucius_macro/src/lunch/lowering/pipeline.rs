@@ -1,42 +1,144 @@
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
 
-use crate::lunch::index::pipeline::PipelineIndex;
 use crate::lunch::lowering::{
-    clinch::lower_clinch, operations::lower_operations, signals::lower_signals,
+    children::lower_children,
+    clinch::lower_clinch,
+    manifest::lower_manifest,
+    operations::lower_operations,
+    params::{config_ident, lower_config, lower_params_consts, lower_params_locals},
+    profiles::{lower_profile_entries, lower_profile_enum},
+    rollup::lower_rollup,
+    rule_mask::rule_names,
+    signals::lower_signals,
+    stages::lower_stages,
+    suppress::{lower_suppress, lower_tag_suppress},
 };
-use syn::parse_quote;
+use lunch_core::index::pipeline::PipelineIndex;
 
 pub fn lower_pipeline(index: &PipelineIndex, component: &str) -> TokenStream2 {
     let fn_ident = format_ident!("run_{}_pipeline", component);
-
+    let in_fn_ident = format_ident!("run_{}_pipeline_in", component);
+    let explained_fn_ident = format_ident!("run_{}_pipeline_explained", component);
+    let with_fn_ident = format_ident!("run_{}_pipeline_with", component);
+    let in_with_fn_ident = format_ident!("run_{}_pipeline_in_with", component);
+    let nested_fn_ident = format_ident!("run_{}_pipeline_nested", component);
+    let config_ident = config_ident(component);
 
     let ops_crate_ident = format_ident!("{}_ops", component);
     let ops_path: syn::Path = syn::parse_quote! {
         crate::#ops_crate_ident
     };
 
-    let ops = lower_operations(&index.operation_index, &ops_path);
-    let signals = lower_signals(&index.signal_index, &index.operation_index);
-    let clinch = lower_clinch(&index.clinch_index);
+    let params_consts = lower_params_consts(&index.param_index, component);
+    let params = lower_params_locals(&index.param_index);
+    let config = lower_config(
+        &index.param_index,
+        component,
+        &rule_names(&index.signal_index, &index.clinch_index),
+    );
+    let ops = lower_operations(&index.operation_index, &ops_path, None);
+    let children = lower_children(&index.operation_index, component, None);
+    let signals = lower_signals(&index.signal_index, &index.operation_index, None);
+    let stages = lower_stages(index, component, &ops_path);
+    let rollup = lower_rollup(&index.rollup_index);
+    let suppress = lower_suppress(
+        &index.suppress_index,
+        &index.signal_index,
+        &index.operation_index,
+        None,
+    );
+    let tag_suppress = lower_tag_suppress(&index.suppress_index);
+    let clinch = lower_clinch(
+        &index.clinch_index,
+        &index.signal_index,
+        &index.operation_index,
+        &index.profile_index,
+        component,
+    );
+    let profile_enum = lower_profile_enum(&index.profile_index, component);
+    let profile_entries = lower_profile_entries(&index.profile_index, component);
+    let manifest = lower_manifest(index, component);
 
     quote! {
+        pub fn #fn_ident(artifact: &Artifact) -> LuciusContext {
+            #in_fn_ident(artifact, LuciusContext::new())
+        }
+
+        /// Same as the plain entry point, but records a provenance trail; see `LuciusContext::explain`.
+        pub fn #explained_fn_ident(artifact: &Artifact) -> LuciusContext {
+            #in_fn_ident(artifact, LuciusContext::explained())
+        }
+
+        /// Run with param values from `config` instead of the declared defaults.
+        pub fn #with_fn_ident(artifact: &Artifact, config: &#config_ident) -> LuciusContext {
+            #in_with_fn_ident(artifact, LuciusContext::new(), config)
+        }
+
+        #profile_entries
+
+        /// Run the pipeline into a caller-prepared context.
+        pub fn #in_fn_ident(artifact: &Artifact, ctx: LuciusContext) -> LuciusContext {
+            #in_with_fn_ident(artifact, ctx, &#config_ident::default())
+        }
+
+        /// Entry point for `run pipeline` in another component. `overrides` are the
+        /// caller's changes from its defaults; see `from_shared_overrides`.
+        pub fn #nested_fn_ident(
+            artifact: &Artifact,
+            ctx: LuciusContext,
+            overrides: &::common::config::ParamOverrides,
+        ) -> LuciusContext {
+            #in_with_fn_ident(artifact, ctx, &#config_ident::from_shared_overrides(overrides))
+        }
+
+        /// Run the pipeline into a caller-prepared context with param values from the config.
+        /// The config is `__lunch_config` so a param or binding called `config` can't shadow it.
         #[allow(non_snake_case)]
-        pub fn #fn_ident(
+        pub fn #in_with_fn_ident(
             artifact: &Artifact,
-        ) -> LuciusContext{
-            let mut ctx = LuciusContext::new();
+            mut ctx: LuciusContext,
+            __lunch_config: &#config_ident,
+        ) -> LuciusContext {
+            // --- params ---
+            #params
+
+            ctx.disabled_rules
+                .extend(__lunch_config.rule_mask.disabled_names().into_iter().map(String::from));
 
             // --- operations ---
             #ops
 
+            // --- children ---
+            #children
+
             // --- signals ---
             #signals
 
+            // --- suppress ---
+            #suppress
+
+            // --- stages ---
+            #stages
+
+            // --- tag suppress ---
+            #tag_suppress
+
+            // --- rollup ---
+            #rollup
+
             // --- clinch ---
             #clinch
 
             ctx
         }
+
+        #params_consts
+
+        #config
+
+        #profile_enum
+
+        #manifest
     }
 }
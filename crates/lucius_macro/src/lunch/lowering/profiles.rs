@@ -0,0 +1,128 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+use crate::lunch::lowering::params::{camel_case, config_ident};
+use lunch_core::index::{
+    clinch::{ScoreValue, SignalId},
+    profiles::ProfileIndex,
+};
+
+/// `<Component>Profile`: `Default` plus one variant per `profile` block.
+pub fn lower_profile_enum(index: &ProfileIndex, component: &str) -> TokenStream2 {
+    let profile_ident = profile_ident(component);
+
+    let variants: Vec<_> = index
+        .profiles
+        .iter()
+        .map(|profile| format_ident!("{}", camel_case(&profile.name)))
+        .collect();
+    let names: Vec<_> = index.profiles.iter().map(|profile| &profile.name).collect();
+
+    quote! {
+        /// Score overlay a run uses; `Default` keeps the values written in clinch.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+        pub enum #profile_ident {
+            #[default]
+            Default,
+            #(#variants,)*
+        }
+
+        impl #profile_ident {
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    Self::Default => "default",
+                    #(Self::#variants => #names,)*
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    "default" => Some(Self::Default),
+                    #(#names => Some(Self::#variants),)*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// `run_<component>_pipeline_<profile>` for each profile.
+pub fn lower_profile_entries(index: &ProfileIndex, component: &str) -> TokenStream2 {
+    let with_fn_ident = format_ident!("run_{}_pipeline_with", component);
+    let config_ident = config_ident(component);
+    let profile_ident = profile_ident(component);
+
+    let entries = index.profiles.iter().map(|profile| {
+        let fn_ident = format_ident!("run_{}_pipeline_{}", component, profile.name);
+        let variant = format_ident!("{}", camel_case(&profile.name));
+        let doc = format!(
+            " Run with the `{}` profile's score overrides.",
+            profile.name
+        );
+
+        quote! {
+            #[doc = #doc]
+            pub fn #fn_ident(artifact: &Artifact) -> LuciusContext {
+                #with_fn_ident(
+                    artifact,
+                    &#config_ident {
+                        profile: #profile_ident::#variant,
+                        ..#config_ident::default()
+                    },
+                )
+            }
+        }
+    });
+
+    quote! {
+        #(#entries)*
+    }
+}
+
+/// The value a `score <key>` action on `signal` adds/sets. Overridden values
+/// branch on `__lunch_config.profile`; everything else stays a plain literal or param.
+pub fn lower_score_value(
+    index: &ProfileIndex,
+    component: &str,
+    signal: &SignalId,
+    key: &str,
+    value: &ScoreValue,
+) -> TokenStream2 {
+    let default = lower_value(value);
+    let profile_ident = profile_ident(component);
+
+    let arms: Vec<_> = index
+        .overrides_for(signal, key)
+        .map(|(profile, value)| {
+            let variant = format_ident!("{}", camel_case(profile));
+            let value = lower_value(value);
+            quote! { #profile_ident::#variant => #value, }
+        })
+        .collect();
+
+    if arms.is_empty() {
+        return default;
+    }
+
+    quote! {
+        match __lunch_config.profile {
+            #(#arms)*
+            _ => #default,
+        }
+    }
+}
+
+/// `lstran` -> `LstranProfile`
+pub fn profile_ident(component: &str) -> syn::Ident {
+    format_ident!("{}Profile", camel_case(component))
+}
+
+fn lower_value(value: &ScoreValue) -> TokenStream2 {
+    match value {
+        ScoreValue::Number(n) => quote! { #n },
+        ScoreValue::Param(param) => {
+            let param = format_ident!("{}", param);
+            quote! { #param }
+        }
+    }
+}
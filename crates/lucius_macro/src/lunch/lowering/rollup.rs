@@ -0,0 +1,25 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use lunch_core::index::rollup::{AggregateFn, RollupIndex, RollupInfo};
+
+/// Runs after child scanning and tag suppressions and before clinch, so clauses
+/// see (and add to) the rolled-up values and suppressed child tags stay out.
+pub fn lower_rollup(index: &RollupIndex) -> TokenStream2 {
+    let rules = index.rules.iter().map(|rule| match rule {
+        RollupInfo::Score { key, func } => {
+            let func = match func {
+                AggregateFn::Max => quote!(f64::max),
+                AggregateFn::Min => quote!(f64::min),
+                AggregateFn::Sum => quote!(|acc: f64, value: f64| acc + value),
+            };
+            quote! { ctx.rollup_score(#key, #func); }
+        }
+        RollupInfo::Tags { prefix } => quote! { ctx.rollup_tags(#prefix); },
+        RollupInfo::Verdict => quote! { ctx.rollup_verdict(); },
+    });
+
+    quote! {
+        #(#rules)*
+    }
+}
@@ -0,0 +1,41 @@
+use lunch_core::index::{clinch::ClinchIndex, signals::SignalIndex};
+
+/// Names behind `RuleMask` bits: signals (`family.name`, sorted) first, then
+/// clinch clauses by label in declaration order.
+pub fn rule_names(signals: &SignalIndex, clinch: &ClinchIndex) -> Vec<String> {
+    let mut names = signal_names(signals);
+    names.extend(clinch.clauses.iter().map(|clause| clause.label()));
+    names
+}
+
+/// Bit of `family.name`: its position in the sorted names `rule_names` starts
+/// with. Only signals from the index are lowered, so it is always there.
+pub fn signal_bit(signals: &SignalIndex, family: &str, name: &str) -> usize {
+    let full = format!("{}.{}", family, name);
+    signal_names(signals)
+        .binary_search(&full)
+        .unwrap_or_else(|_| unreachable!("signal `{}` is not in the signal index", full))
+}
+
+/// Bit of the clause at `position` in the clinch block.
+pub fn clause_bit(signals: &SignalIndex, position: usize) -> usize {
+    signal_count(signals) + position
+}
+
+fn signal_count(signals: &SignalIndex) -> usize {
+    signals.families.values().map(|f| f.signals.len()).sum()
+}
+
+fn signal_names(signals: &SignalIndex) -> Vec<String> {
+    let mut names: Vec<String> = signals
+        .families
+        .iter()
+        .flat_map(|(family, info)| {
+            info.signals
+                .keys()
+                .map(move |name| format!("{}.{}", family, name))
+        })
+        .collect();
+    names.sort();
+    names
+}
@@ -1,17 +1,25 @@
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
 
-use crate::lunch::index::{
+use crate::lunch::lowering::rule_mask::signal_bit;
+use lunch_core::index::{
     operations::OperationIndex,
-    signals::{SignalIndex, SignalInfo},
+    signals::{DeriveFrom, SignalIndex, SignalInfo},
 };
 
-pub fn lower_signals(index: &SignalIndex, ops: &OperationIndex) -> TokenStream2 {
+/// Signals declared in `stage` (`None` for top-level `signals`).
+pub fn lower_signals(
+    index: &SignalIndex,
+    ops: &OperationIndex,
+    stage: Option<&str>,
+) -> TokenStream2 {
     let mut lowered = Vec::new();
 
     for (family_name, family) in &index.families {
         for (signal_name, signal) in &family.signals {
-            lowered.push(lower_signal(family_name, signal_name, signal, ops));
+            if signal.stage.as_deref() == stage {
+                lowered.push(lower_signal(index, family_name, signal_name, signal, ops));
+            }
         }
     }
 
@@ -22,31 +30,74 @@ pub fn lower_signals(index: &SignalIndex, ops: &OperationIndex) -> TokenStream2
 }
 
 fn lower_signal(
+    index: &SignalIndex,
     family_name: &str,
     signal_name: &str,
     sig: &SignalInfo,
     ops: &OperationIndex,
 ) -> TokenStream2 {
     let sig_ident = format_ident!("__signal_{}_{}", family_name, signal_name);
+    let bit = signal_bit(index, family_name, signal_name);
+
+    let when_tokens = &sig.when;
+    let cfg = &sig.cfg;
 
-    let op_name = &sig.derives_from.operation;
-    let step_name = &sig.derives_from.step;
+    let when = with_step_binding(
+        &sig.derives_from,
+        ops,
+        quote! { #when_tokens },
+        quote! { false },
+    );
 
-    let step_result_ident = format_ident!("__op_{}_step_{}", op_name, step_name);
+    quote! {
+        #(#cfg)*
+        let #sig_ident: bool = __lunch_config.rule_mask.is_enabled(#bit) && #when;
+    }
+}
 
+fn step_binding_ident(derives_from: &DeriveFrom, ops: &OperationIndex) -> syn::Ident {
     let binding_name = ops
         .index
-        .get(op_name)
-        .and_then(|op| op.steps.get(step_name))
-        .and_then(|step| Some(step.binding.clone()))
-        .unwrap_or(step_name.to_string());
+        .get(&derives_from.operation)
+        .and_then(|op| op.steps.get(&derives_from.step))
+        .map(|step| step.binding.clone())
+        .unwrap_or(derives_from.step.to_string());
 
-    let step_alias_ident = format_ident!("{}", binding_name);
+    format_ident!("{}", binding_name)
+}
 
-    let when_tokens = &sig.when;
+/// `body` with the step's own output bound under its binding name. Every
+/// reader binds it locally, so two steps sharing an output name never see each
+/// other's result. A gated step's result is an `Option`; `fallback` stands in
+/// when its stage did not run.
+pub fn with_step_binding(
+    derives_from: &DeriveFrom,
+    ops: &OperationIndex,
+    body: TokenStream2,
+    fallback: TokenStream2,
+) -> TokenStream2 {
+    let result_ident = format_ident!("__op_{}_step_{}", derives_from.operation, derives_from.step);
+    let alias_ident = step_binding_ident(derives_from, ops);
+
+    if !ops
+        .index
+        .get(&derives_from.operation)
+        .is_some_and(|op| op.gated)
+    {
+        return quote! {
+            {
+                #[allow(unused_variables)]
+                let #alias_ident = &#result_ident;
+                #body
+            }
+        };
+    }
 
     quote! {
-        let #step_alias_ident = &#step_result_ident;
-        let #sig_ident: bool = { #when_tokens };
+        match &#result_ident {
+            #[allow(unused_variables)]
+            Some(#alias_ident) => { #body }
+            None => #fallback,
+        }
     }
 }
@@ -0,0 +1,53 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+use crate::lunch::lowering::{
+    children::lower_children, operations::lower_operations, signals::lower_signals,
+    suppress::lower_suppress,
+};
+use lunch_core::index::pipeline::PipelineIndex;
+
+/// Each stage lowers in place, after the top level and the stages before it, so
+/// its gate and signals can read anything declared earlier, already suppressed. The gate is a plain
+/// bool; gated ops are wrapped in `if __stage_<name> { ... }` and yield `Option`s.
+pub fn lower_stages(index: &PipelineIndex, component: &str, ops_path: &syn::Path) -> TokenStream2 {
+    let stages = index.stage_index.stages.iter().map(|stage| {
+        let name = stage.name.as_str();
+        let stage_ident = format_ident!("__stage_{}", name);
+
+        let gate = match &stage.gate {
+            Some(gate) => {
+                let sig_ident = format_ident!("__signal_{}_{}", gate.family, gate.name);
+                quote! {
+                    let #stage_ident: bool = #sig_ident;
+                    if !#stage_ident {
+                        ctx.skipped_stages.push(#name.to_string());
+                    }
+                }
+            }
+            None => quote! {},
+        };
+
+        let ops = lower_operations(&index.operation_index, ops_path, Some(name));
+        let children = lower_children(&index.operation_index, component, Some(name));
+        let signals = lower_signals(&index.signal_index, &index.operation_index, Some(name));
+        let suppress = lower_suppress(
+            &index.suppress_index,
+            &index.signal_index,
+            &index.operation_index,
+            Some(name),
+        );
+
+        quote! {
+            #gate
+            #ops
+            #children
+            #signals
+            #suppress
+        }
+    });
+
+    quote! {
+        #(#stages)*
+    }
+}
@@ -0,0 +1,173 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{ToTokens, format_ident, quote};
+use syn::{
+    BinOp, Expr, Member, parse_quote,
+    visit_mut::{self, VisitMut},
+};
+
+use crate::lunch::lowering::signals::with_step_binding;
+use lunch_core::index::{
+    operations::OperationIndex,
+    signals::SignalIndex,
+    suppress::{SignalSuppression, SuppressIndex, TagSuppression},
+};
+
+/// Runs right after the signals of `stage` (`None` for the top level), before any
+/// stage gate reads them. Suppressed signals are shadowed to `false`.
+pub fn lower_suppress(
+    index: &SuppressIndex,
+    signals: &SignalIndex,
+    ops: &OperationIndex,
+    stage: Option<&str>,
+) -> TokenStream2 {
+    let suppressions = index
+        .signals
+        .iter()
+        .filter(|suppression| {
+            signals
+                .families
+                .get(&suppression.signal.family)
+                .and_then(|family| family.signals.get(&suppression.signal.name))
+                .is_some_and(|sig| sig.stage.as_deref() == stage)
+        })
+        .map(|suppression| lower_signal_suppression(suppression, signals, ops));
+
+    quote! {
+        #(#suppressions)*
+    }
+}
+
+/// Runs before clinch. Allowlisted tags are registered so `push_tag` drops them.
+pub fn lower_tag_suppress(index: &SuppressIndex) -> TokenStream2 {
+    let tags = index.tags.iter().map(lower_tag_suppression);
+
+    quote! {
+        #(#tags)*
+    }
+}
+
+fn lower_signal_suppression(
+    suppression: &SignalSuppression,
+    signals: &SignalIndex,
+    ops: &OperationIndex,
+) -> TokenStream2 {
+    let family = &suppression.signal.family;
+    let name = &suppression.signal.name;
+    let sig_ident = format_ident!("__signal_{}_{}", family, name);
+    let label = format!("signal.{}.{}", family, name);
+    let when_tokens = lower_meta_index(&suppression.when);
+
+    let when = match signals
+        .families
+        .get(family)
+        .and_then(|f| f.signals.get(name))
+    {
+        Some(sig) => with_step_binding(
+            &sig.derives_from,
+            ops,
+            quote! { #when_tokens },
+            quote! { false },
+        ),
+        None => quote! { { #when_tokens } },
+    };
+
+    quote! {
+        let #sig_ident: bool = if #sig_ident && #when {
+            ctx.suppressed.push(#label.to_string());
+            false
+        } else {
+            #sig_ident
+        };
+    }
+}
+
+/// `artifact.meta[key]` would panic on an artifact without `key`, which is most
+/// of them. Compared with `==` or `!=` it becomes an `Option` comparison, so a
+/// missing key equals nothing. Anywhere else a missing key reads as `""`.
+fn lower_meta_index(when: &TokenStream2) -> TokenStream2 {
+    match syn::parse2::<Expr>(when.clone()) {
+        Ok(mut expr) => {
+            MetaLookups.visit_expr_mut(&mut expr);
+            expr.into_token_stream()
+        }
+        Err(_) => when.clone(),
+    }
+}
+
+struct MetaLookups;
+
+impl VisitMut for MetaLookups {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Expr::Binary(binary) = expr
+            && matches!(binary.op, BinOp::Eq(_) | BinOp::Ne(_))
+        {
+            let (key, other, key_on_left) = match (meta_key(&binary.left), meta_key(&binary.right))
+            {
+                (Some(key), _) => (key, &mut *binary.right, true),
+                (None, Some(key)) => (key, &mut *binary.left, false),
+                (None, None) => return visit_mut::visit_expr_binary_mut(self, binary),
+            };
+            self.visit_expr_mut(other);
+
+            let lookup: Expr = parse_quote! {
+                artifact.meta.get(#key).map(::std::string::String::as_str)
+            };
+            let value: Expr = parse_quote! {
+                ::std::option::Option::Some(::core::convert::AsRef::<str>::as_ref(&(#other)))
+            };
+            let (left, right) = if key_on_left {
+                (lookup, value)
+            } else {
+                (value, lookup)
+            };
+            *binary.left = left;
+            *binary.right = right;
+            return;
+        }
+
+        if let Some(key) = meta_key(expr) {
+            *expr = parse_quote! {
+                artifact
+                    .meta
+                    .get(#key)
+                    .map(::std::string::String::as_str)
+                    .unwrap_or_default()
+            };
+            return;
+        }
+
+        visit_mut::visit_expr_mut(self, expr);
+    }
+}
+
+/// `key` when `expr` is `artifact.meta[key]`.
+fn meta_key(expr: &Expr) -> Option<Expr> {
+    let Expr::Index(index) = expr else {
+        return None;
+    };
+    let Expr::Field(field) = &*index.expr else {
+        return None;
+    };
+    let is_meta = matches!(&field.member, Member::Named(name) if name == "meta")
+        && matches!(&*field.base, Expr::Path(path) if path.path.is_ident("artifact"));
+
+    is_meta.then(|| (*index.index).clone())
+}
+
+fn lower_tag_suppression(suppression: &TagSuppression) -> TokenStream2 {
+    let tag = &suppression.tag;
+    let meta_key = &suppression.meta_key;
+    let list = format!("/{}", suppression.list);
+
+    quote! {
+        if artifact.meta.get(#meta_key).is_some_and(|value| {
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), #list))
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .any(|line| line == value)
+        }) {
+            ctx.suppressed_tags.push(#tag.to_string());
+        }
+    }
+}
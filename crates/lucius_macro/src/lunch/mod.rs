@@ -1,5 +1,2 @@
 pub mod codegen;
-pub mod index;
 pub mod lowering;
-pub mod parse;
-pub mod validate;
@@ -1,4 +0,0 @@
-pub mod clinch;
-pub mod operations;
-pub mod pipeline;
-pub mod signals;
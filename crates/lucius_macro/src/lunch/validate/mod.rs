@@ -1,4 +0,0 @@
-pub mod clinch;
-pub mod core_validate;
-pub mod operations;
-pub mod signals;
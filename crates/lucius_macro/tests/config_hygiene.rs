@@ -0,0 +1,88 @@
+//! A param and a step binding may be called `config` without shadowing the
+//! config the generated run fn reads its rule mask and profile from.
+
+use common::data_objs::{Artifact, LuciusContext};
+use lucius_macro::lunch;
+
+mod hygiene_ops {
+    use common::data_objs::Artifact;
+
+    pub struct Settings {
+        pub strict: bool,
+    }
+
+    pub fn read_settings(artifact: &Artifact) -> Settings {
+        Settings {
+            strict: artifact.meta.contains_key("strict"),
+        }
+    }
+}
+
+lunch! {
+    component = hygiene
+
+    {
+
+    params {
+        config: bool = true
+    }
+
+    operations {
+        operation settings {
+            do read_settings output config
+        }
+    }
+
+    signals {
+        family mode {
+            signal strict {
+                derive from operation.settings.read_settings
+                    when config.strict
+            }
+        }
+    }
+
+    clinch {
+        when signal.mode.strict {
+            score strictness += 1.0
+        }
+    }
+
+    profile lax {
+        score strictness for signal.mode.strict = 0.5
+    }
+
+    }
+}
+
+fn artifact() -> Artifact {
+    Artifact {
+        bytes: Vec::new(),
+        text: None,
+        meta: [("strict".to_string(), String::new())].into(),
+    }
+}
+
+#[test]
+fn binding_named_config_is_read() {
+    let ctx = run_hygiene_pipeline(&artifact());
+
+    assert_eq!(ctx.scores["strictness"], 1.0);
+}
+
+#[test]
+fn profile_is_read_past_a_param_named_config() {
+    let ctx = run_hygiene_pipeline_lax(&artifact());
+
+    assert_eq!(ctx.scores["strictness"], 0.5);
+}
+
+#[test]
+fn rule_mask_is_read_past_a_param_named_config() {
+    let mut config = HygieneConfig::default();
+    config.rule_mask.disable("mode.strict");
+    let ctx = run_hygiene_pipeline_with(&artifact(), &config);
+
+    assert!(ctx.scores.is_empty());
+    assert_eq!(ctx.disabled_rules, ["mode.strict"]);
+}
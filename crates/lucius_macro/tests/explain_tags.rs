@@ -0,0 +1,71 @@
+//! Attribution and the explain trail record a tag as it was pushed, with its
+//! interpolations filled in, not as its template.
+
+use common::data_objs::{Artifact, LuciusContext};
+use lucius_macro::lunch;
+
+mod label_ops {
+    use common::data_objs::Artifact;
+
+    pub struct Kind {
+        pub name: &'static str,
+    }
+
+    pub fn classify(artifact: &Artifact) -> Kind {
+        Kind {
+            name: if artifact.bytes.starts_with(b"%PDF") {
+                "pdf"
+            } else {
+                "other"
+            },
+        }
+    }
+}
+
+lunch! {
+    component = label
+
+    {
+
+    operations {
+        operation kind {
+            do classify output kind
+        }
+    }
+
+    signals {
+        family format {
+            signal known {
+                derive from operation.kind.classify
+                    when kind.name != "other"
+            }
+        }
+    }
+
+    clinch {
+        when signal.format.known {
+            tag += "classified:{kind.name}"
+            tag format = kind.name
+            tag += "known"
+        }
+    }
+
+    }
+}
+
+#[test]
+fn trail_records_rendered_tags() {
+    let artifact = Artifact {
+        bytes: b"%PDF-1.7".to_vec(),
+        text: None,
+        meta: Default::default(),
+    };
+    let ctx = run_label_pipeline_explained(&artifact);
+
+    let pushed = [r#"tag:"classified:pdf""#, "format:pdf", r#"tag:"known""#];
+    assert_eq!(ctx.tags, pushed);
+    let attributed: Vec<&str> = ctx.attributions.iter().map(|a| a.action.as_str()).collect();
+    assert_eq!(attributed, pushed);
+    let recorded: Vec<&str> = ctx.provenance.iter().map(|p| p.action.as_str()).collect();
+    assert_eq!(recorded, pushed);
+}
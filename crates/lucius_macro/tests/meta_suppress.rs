@@ -0,0 +1,99 @@
+//! `artifact.meta[..]` in a suppression never panics, whether or not the
+//! artifact carries the key.
+
+use common::data_objs::{Artifact, LuciusContext};
+use lucius_macro::lunch;
+
+mod meta_ops {
+    use common::data_objs::Artifact;
+
+    pub struct Size {
+        pub len: usize,
+    }
+
+    pub fn measure(artifact: &Artifact) -> Size {
+        Size {
+            len: artifact.bytes.len(),
+        }
+    }
+}
+
+lunch! {
+    component = meta
+
+    {
+
+    operations {
+        operation size {
+            do measure output size
+        }
+    }
+
+    signals {
+        family shape {
+            signal any {
+                derive from operation.size.measure
+                    when size.len > 0
+            }
+
+            signal small {
+                derive from operation.size.measure
+                    when size.len < 100
+            }
+        }
+    }
+
+    suppress {
+        signal.shape.any
+            when artifact.meta["signer"] == "Contoso"
+
+        signal.shape.small
+            when artifact.meta["zone"].starts_with("intranet")
+    }
+
+    clinch {
+        when signal.shape.any {
+            tag += "any"
+        }
+
+        when signal.shape.small {
+            tag += "small"
+        }
+    }
+
+    }
+}
+
+fn artifact(meta: &[(&str, &str)]) -> Artifact {
+    Artifact {
+        bytes: vec![0; 4],
+        text: None,
+        meta: meta
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect(),
+    }
+}
+
+#[test]
+fn present_key_suppresses() {
+    let ctx = run_meta_pipeline(&artifact(&[("signer", "Contoso"), ("zone", "intranet-a")]));
+
+    assert_eq!(ctx.suppressed, ["signal.shape.any", "signal.shape.small"]);
+    assert!(ctx.tags.is_empty());
+}
+
+#[test]
+fn missing_key_matches_nothing() {
+    let ctx = run_meta_pipeline(&artifact(&[]));
+
+    assert!(ctx.suppressed.is_empty());
+    assert_eq!(ctx.tags, [r#"tag:"any""#, r#"tag:"small""#]);
+}
+
+#[test]
+fn other_value_does_not_suppress() {
+    let ctx = run_meta_pipeline(&artifact(&[("signer", "Fabrikam"), ("zone", "dmz")]));
+
+    assert!(ctx.suppressed.is_empty());
+}
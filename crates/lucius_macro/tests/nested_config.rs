@@ -0,0 +1,185 @@
+//! `run pipeline` hands the caller's config changes to the nested component,
+//! and everything the nested run produced, its children included, comes back
+//! under the component's namespace.
+
+use common::{
+    config::ParamOverrides,
+    data_objs::{Artifact, LuciusContext},
+};
+use lucius_macro::lunch;
+
+mod outer_ops {
+    use common::data_objs::Artifact;
+
+    pub struct Size {
+        pub len: usize,
+    }
+
+    pub fn measure(artifact: &Artifact) -> Size {
+        Size {
+            len: artifact.bytes.len(),
+        }
+    }
+
+    /// Every byte of a multi-byte artifact, as its own child.
+    pub fn split(artifact: &Artifact) -> Vec<Artifact> {
+        if artifact.bytes.len() < 2 {
+            return Vec::new();
+        }
+        artifact
+            .bytes
+            .iter()
+            .map(|&byte| Artifact {
+                bytes: vec![byte],
+                text: None,
+                meta: Default::default(),
+            })
+            .collect()
+    }
+}
+
+mod inner_ops {
+    pub use crate::outer_ops::{measure, split};
+}
+
+lunch! {
+    component = outer
+
+    {
+
+    params {
+        LIMIT: usize = 100
+    }
+
+    operations {
+        operation size {
+            do measure output size
+        }
+
+        operation parts {
+            do split output parts children
+        }
+    }
+
+    signals {
+        family shape {
+            signal big {
+                derive from operation.size.measure
+                    when size.len > LIMIT
+            }
+
+            signal any {
+                derive from operation.size.measure
+                    when size.len > 0
+            }
+        }
+    }
+
+    clinch {
+        when signal.shape.big {
+            tag += "wide"
+        }
+
+        when signal.shape.any {
+            run pipeline inner
+        }
+    }
+
+    }
+}
+
+lunch! {
+    component = inner
+
+    {
+
+    params {
+        LIMIT: usize = 10
+    }
+
+    operations {
+        operation size {
+            do measure output size
+        }
+
+        operation parts {
+            do split output parts children
+        }
+    }
+
+    signals {
+        family shape {
+            signal big {
+                derive from operation.size.measure
+                    when size.len > LIMIT
+            }
+        }
+    }
+
+    clinch {
+        when signal.shape.big {
+            tag += "big"
+        }
+    }
+
+    }
+}
+
+fn artifact() -> Artifact {
+    Artifact {
+        bytes: vec![1, 2, 3],
+        text: None,
+        meta: Default::default(),
+    }
+}
+
+#[test]
+fn nested_run_uses_its_own_defaults() {
+    let ctx = run_outer_pipeline(&artifact());
+
+    assert!(ctx.tags.is_empty());
+}
+
+#[test]
+fn nested_run_gets_the_callers_param() {
+    let config = OuterConfig {
+        LIMIT: 2,
+        ..OuterConfig::default()
+    };
+    let ctx = run_outer_pipeline_with(&artifact(), &config);
+
+    assert_eq!(ctx.tags, [r#"tag:"wide""#, r#"inner.tag:"big""#]);
+}
+
+#[test]
+fn nested_run_gets_the_callers_disabled_rules() {
+    let mut overrides = ParamOverrides::default();
+    overrides
+        .values
+        .insert("LIMIT".to_string(), common::config::ParamValue::Int(2));
+    overrides.disabled.push("shape.big".to_string());
+    let config = OuterConfig::from_overrides(&overrides).unwrap();
+    let ctx = run_outer_pipeline_with(&artifact(), &config);
+
+    assert!(ctx.tags.is_empty());
+    assert_eq!(ctx.disabled_rules, ["shape.big", "inner.shape.big"]);
+}
+
+#[test]
+fn nested_children_are_namespaced() {
+    let ctx = run_outer_pipeline(&artifact());
+
+    let sources: Vec<&str> = ctx.children.iter().map(|c| c.source.as_str()).collect();
+    assert_eq!(
+        sources,
+        [
+            "parts.split",
+            "parts.split",
+            "parts.split",
+            "inner.parts.split",
+            "inner.parts.split",
+            "inner.parts.split",
+        ]
+    );
+    assert_eq!(ctx.scanned_children, 6);
+}
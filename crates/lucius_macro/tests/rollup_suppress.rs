@@ -0,0 +1,107 @@
+//! A tag suppression holds for tags rolled up from children, not just for the
+//! ones the parent's own clauses push.
+
+use common::data_objs::{Artifact, LuciusContext};
+use lucius_macro::lunch;
+
+mod nest_ops {
+    use common::data_objs::Artifact;
+
+    pub struct Size {
+        pub len: usize,
+    }
+
+    pub fn measure(artifact: &Artifact) -> Size {
+        Size {
+            len: artifact.bytes.len(),
+        }
+    }
+
+    /// Every byte of a multi-byte artifact, as its own child.
+    pub fn split(artifact: &Artifact) -> Vec<Artifact> {
+        if artifact.bytes.len() < 2 {
+            return Vec::new();
+        }
+        artifact
+            .bytes
+            .iter()
+            .map(|&byte| Artifact {
+                bytes: vec![byte],
+                text: None,
+                meta: Default::default(),
+            })
+            .collect()
+    }
+}
+
+lunch! {
+    component = nest
+
+    {
+
+    operations {
+        operation size {
+            do measure output size
+        }
+
+        operation parts {
+            do split output parts children
+        }
+    }
+
+    signals {
+        family shape {
+            signal leaf {
+                derive from operation.size.measure
+                    when size.len == 1
+            }
+        }
+    }
+
+    suppress {
+        tag kind = "leaf" for signer in "tests/data/signers.txt"
+    }
+
+    rollup {
+        tags: union
+    }
+
+    clinch {
+        when signal.shape.leaf {
+            tag kind = "leaf"
+        }
+    }
+
+    }
+}
+
+fn artifact(signer: &str) -> Artifact {
+    Artifact {
+        bytes: vec![1, 2],
+        text: None,
+        meta: [("signer".to_string(), signer.to_string())].into(),
+    }
+}
+
+#[test]
+fn child_tag_rolls_up() {
+    let ctx = run_nest_pipeline(&artifact("Fabrikam"));
+
+    assert_eq!(ctx.children.len(), 2);
+    assert_eq!(ctx.tags, ["kind:leaf"]);
+    assert!(ctx.suppressed.is_empty());
+}
+
+#[test]
+fn suppressed_tag_does_not_roll_up() {
+    let ctx = run_nest_pipeline(&artifact("Contoso"));
+
+    assert_eq!(ctx.children.len(), 2);
+    assert!(
+        ctx.children
+            .iter()
+            .all(|child| child.context.tags == ["kind:leaf"])
+    );
+    assert!(ctx.tags.is_empty());
+    assert_eq!(ctx.suppressed, ["tag:kind:leaf"]);
+}
@@ -0,0 +1,84 @@
+//! Two operations whose steps share an output name. Each signal and clause
+//! must read its own step's result, whatever order the ops are lowered in.
+
+use common::data_objs::{Artifact, LuciusContext};
+use lucius_macro::lunch;
+
+mod shared_ops {
+    use common::data_objs::Artifact;
+
+    pub struct Length {
+        pub len: usize,
+    }
+
+    pub struct First {
+        pub byte: u8,
+    }
+
+    pub fn measure(artifact: &Artifact) -> Length {
+        Length {
+            len: artifact.bytes.len(),
+        }
+    }
+
+    pub fn first_byte(artifact: &Artifact) -> First {
+        First {
+            byte: artifact.bytes.first().copied().unwrap_or_default(),
+        }
+    }
+}
+
+lunch! {
+    component = shared
+
+    {
+
+    operations {
+        operation size {
+            do measure output probe
+        }
+
+        operation header {
+            do first_byte output probe
+        }
+    }
+
+    signals {
+        family shape {
+            signal long {
+                derive from operation.size.measure
+                    when probe.len > 3
+            }
+
+            signal mz {
+                derive from operation.header.first_byte
+                    when probe.byte == 0x4D
+            }
+        }
+    }
+
+    clinch {
+        when signal.shape.long {
+            tag length = probe.len
+        }
+
+        when signal.shape.mz {
+            tag first = probe.byte
+        }
+    }
+
+    }
+}
+
+#[test]
+fn steps_sharing_an_output_name_read_their_own_result() {
+    let artifact = Artifact {
+        bytes: b"MZ\x90\x00".to_vec(),
+        text: None,
+        meta: Default::default(),
+    };
+
+    let ctx = run_shared_pipeline(&artifact);
+
+    assert_eq!(ctx.tags, ["length:4", "first:77"]);
+}
@@ -0,0 +1,99 @@
+/*
+
+Error accumulation. Parsing recovers at item boundaries (a block, a clause, a
+`do` statement) and validation keeps going past an item it rejected, so one
+expansion reports every mistake in a rule pack, each at its own span.
+
+*/
+
+use proc_macro2::{Delimiter, TokenTree};
+use syn::parse::{Parse, ParseStream, discouraged::Speculative};
+use syn::{Error, Result};
+
+#[derive(Default)]
+pub struct Diagnostics {
+    error: Option<Error>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, err: Error) {
+        match &mut self.error {
+            Some(error) => error.combine(err),
+            None => self.error = Some(err),
+        }
+    }
+
+    /// Record the error of `result`, if any, and hand back its value.
+    pub fn check<T>(&mut self, result: Result<T>) -> Option<T> {
+        result.map_err(|err| self.push(err)).ok()
+    }
+
+    /// For errors nothing downstream can recover from: everything collected so
+    /// far, plus `err`.
+    pub fn bail(&mut self, err: Error) -> Error {
+        self.push(err);
+        self.error.take().expect("error was just pushed")
+    }
+
+    pub fn finish(self) -> Result<()> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parse a `... { ... }` item. On failure the error is recorded and the input
+/// skips past the item's first brace group, where the next item starts.
+pub fn parse_item<T: Parse>(input: ParseStream, diags: &mut Diagnostics) -> Option<T> {
+    let fork = input.fork();
+    match fork.parse::<T>() {
+        Ok(item) => {
+            input.advance_to(&fork);
+            Some(item)
+        }
+        Err(err) => {
+            diags.push(err);
+            skip_item(input);
+            None
+        }
+    }
+}
+
+/// Skip up to and including the next brace group.
+pub fn skip_item(input: ParseStream) {
+    while let Ok(tt) = input.parse::<TokenTree>() {
+        if matches!(&tt, TokenTree::Group(g) if g.delimiter() == Delimiter::Brace) {
+            break;
+        }
+    }
+}
+
+/// Parse an item without a body of its own (a `do` statement, a clinch action).
+/// On failure the error is recorded and the input skips to where `at_next` holds.
+pub fn parse_until<T: Parse>(
+    input: ParseStream,
+    diags: &mut Diagnostics,
+    at_next: fn(ParseStream) -> bool,
+) -> Option<T> {
+    let fork = input.fork();
+    match fork.parse::<T>() {
+        Ok(item) => {
+            input.advance_to(&fork);
+            Some(item)
+        }
+        Err(err) => {
+            diags.push(err);
+            // always make progress, even when the bad token is itself a start token
+            let _ = input.parse::<TokenTree>();
+            while !input.is_empty() && !at_next(input) {
+                let _ = input.parse::<TokenTree>();
+            }
+            None
+        }
+    }
+}
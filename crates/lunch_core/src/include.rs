@@ -0,0 +1,463 @@
+/*
+
+External rule files. `lunch!(file = "rules/lstran.lunch")` reads the whole
+invocation from a file, and `include "families/risk.lunch"` splices a file into
+the block it sits in. Paths are relative to the crate's Cargo.toml, same as
+suppress allowlists. Splicing happens on tokens, before parsing, so the parser
+never sees an `include`.
+
+Only an `include` where a block item can start is spliced: first in a block, or
+right after a `}`, a `;` or another include. One inside a `when` condition, a
+tag expression or any parenthesized or bracketed group is left as written.
+
+Stable proc macros can't span a token in another file. Tokens read from a file
+take the span of the literal in the invocation that pulled them in, and the
+`Loader` remembers which literal read which file, so `origin` can name the file
+a diagnostic came from. Outside a proc macro, proc-macro2 lexes on its fallback,
+whose spans carry lines: a locating `Loader` keeps each file's own spans and
+`location` maps them back to `path:line:column`.
+
+*/
+
+use proc_macro2::{Delimiter, Group, LineColumn, Span, TokenStream as TokenStream2, TokenTree};
+use std::{collections::HashMap, fs, path::PathBuf, str::FromStr};
+use syn::{
+    Error, Lit, LitStr, Result, Token,
+    parse::{Parse, ParseStream},
+};
+
+use crate::{diagnostics::Diagnostics, parse::kw};
+
+pub struct Loader {
+    manifest_dir: PathBuf,
+    /// Every file read, as written and without duplicates.
+    pub files: Vec<String>,
+    /// Files being spliced right now, to catch include cycles.
+    stack: Vec<PathBuf>,
+    /// Keep each file's own spans instead of the literal's. Only meaningful on
+    /// the fallback lexer.
+    locating: bool,
+    /// Fallback source names (`<parsed string N>`) to the files they were read from.
+    sources: HashMap<String, String>,
+    /// Where each literal in the invocation that read a file starts and ends.
+    origins: Vec<(LineColumn, LineColumn, String)>,
+    /// Text to use instead of what's on disk, by canonical path.
+    overlay: HashMap<PathBuf, String>,
+}
+
+impl Default for Loader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Loader {
+    pub fn new() -> Self {
+        Self::with_mode(false)
+    }
+
+    pub fn locating() -> Self {
+        Self::with_mode(true)
+    }
+
+    fn with_mode(locating: bool) -> Self {
+        Self {
+            manifest_dir: std::env::var_os("CARGO_MANIFEST_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_default(),
+            files: Vec::new(),
+            stack: Vec::new(),
+            locating,
+            sources: HashMap::new(),
+            origins: Vec::new(),
+            overlay: HashMap::new(),
+        }
+    }
+
+    /// Resolve paths against `manifest_dir` instead of `CARGO_MANIFEST_DIR`, for
+    /// tools that don't run under cargo.
+    pub fn in_dir(mut self, manifest_dir: PathBuf) -> Self {
+        self.manifest_dir = manifest_dir;
+        self
+    }
+
+    /// Read these files from memory instead of disk, like an editor's unsaved
+    /// buffers. Keys are canonical paths.
+    pub fn with_overlay(mut self, overlay: HashMap<PathBuf, String>) -> Self {
+        self.overlay = overlay;
+        self
+    }
+
+    /// The macro input with `file = ".."` and every `include ".."` resolved.
+    pub fn resolve(&mut self, input: TokenStream2) -> Result<TokenStream2> {
+        let mut diags = Diagnostics::new();
+
+        let first = input.clone().into_iter().next();
+        let tokens = if matches!(first, Some(TokenTree::Ident(ident)) if ident == "file") {
+            let FileInput { path } = syn::parse2(input)?;
+            self.load(&path, &mut diags).unwrap_or_default()
+        } else {
+            self.splice(input, &mut diags)
+        };
+
+        diags.finish()?;
+        Ok(tokens)
+    }
+
+    /// The file a span on the fallback lexer is in, as its `include` wrote it.
+    pub fn file_of(&self, span: Span) -> Option<&str> {
+        self.sources.get(&span.file()).map(String::as_str)
+    }
+
+    /// The file a token spliced in by a non-locating `Loader` was read from, as
+    /// the invocation wrote it. A file included by another file counts as the
+    /// outer one, since its tokens carry the same span.
+    pub fn origin(&self, span: Span) -> Option<&str> {
+        let (start, end) = (span.start(), span.end());
+        self.origins
+            .iter()
+            .find(|(s, e, _)| *s == start && *e == end)
+            .map(|(_, _, path)| path.as_str())
+    }
+
+    /// `path:line:column` of a span on the fallback lexer, if it's in a file.
+    pub fn location(&self, span: Span) -> Option<String> {
+        let path = self.file_of(span)?;
+        let start = span.start();
+        Some(format!("{}:{}:{}", path, start.line, start.column + 1))
+    }
+
+    fn load(&mut self, path: &LitStr, diags: &mut Diagnostics) -> Option<TokenStream2> {
+        let name = path.value();
+        let full = self.manifest_dir.join(&name);
+        let key = full.canonicalize().unwrap_or_else(|_| full.clone());
+
+        let read = match self.overlay.get(&key) {
+            Some(text) => Ok(text.clone()),
+            None => fs::read_to_string(&full),
+        };
+        let text = match read {
+            Ok(text) => text,
+            Err(err) => {
+                diags.push(Error::new(
+                    path.span(),
+                    format!("cannot read `{}`: {}", name, err),
+                ));
+                return None;
+            }
+        };
+
+        if self.stack.contains(&key) {
+            diags.push(Error::new(
+                path.span(),
+                format!("`{}` includes itself", name),
+            ));
+            return None;
+        }
+
+        if !self.files.contains(&name) {
+            self.files.push(name.clone());
+        }
+
+        let tokens = match TokenStream2::from_str(&text) {
+            Ok(tokens) => tokens,
+            Err(err) => {
+                let span = if self.locating {
+                    self.sources.insert(err.span().file(), name.clone());
+                    err.span()
+                } else {
+                    path.span()
+                };
+                diags.push(Error::new(
+                    span,
+                    format!("`{}` is not valid token syntax", name),
+                ));
+                return None;
+            }
+        };
+
+        let tokens = if self.locating {
+            if let Some(first) = tokens.clone().into_iter().next() {
+                self.sources.insert(first.span().file(), name);
+            }
+            tokens
+        } else {
+            let (start, end) = (path.span().start(), path.span().end());
+            // Toolchains without span locations report every span at 0:0.
+            if self.stack.is_empty() && start != end {
+                self.origins.push((start, end, name));
+            }
+            respan(tokens, path.span())
+        };
+
+        self.stack.push(key);
+        let tokens = self.splice(tokens, diags);
+        self.stack.pop();
+
+        Some(tokens)
+    }
+
+    fn splice(&mut self, stream: TokenStream2, diags: &mut Diagnostics) -> TokenStream2 {
+        let mut out = Vec::new();
+        let mut tokens = stream.into_iter().peekable();
+        // Whether a block item could start here.
+        let mut item_start = true;
+
+        while let Some(tt) = tokens.next() {
+            match tt {
+                TokenTree::Ident(ident) if ident == "include" && item_start => {
+                    if let Some(TokenTree::Literal(lit)) = tokens.peek()
+                        && let Lit::Str(path) = Lit::new(lit.clone())
+                    {
+                        tokens.next();
+                        out.extend(self.load(&path, diags).unwrap_or_default());
+                    } else {
+                        item_start = false;
+                        out.push(TokenTree::Ident(ident));
+                    }
+                }
+                TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                    let mut spliced =
+                        Group::new(group.delimiter(), self.splice(group.stream(), diags));
+                    spliced.set_span(group.span());
+                    out.push(spliced.into());
+                    item_start = true;
+                }
+                TokenTree::Punct(punct) if punct.as_char() == ';' => {
+                    out.push(punct.into());
+                    item_start = true;
+                }
+                tt => {
+                    out.push(tt);
+                    item_start = false;
+                }
+            }
+        }
+
+        out.into_iter().collect()
+    }
+}
+
+/// `file = "<path>"`, the whole invocation.
+struct FileInput {
+    path: LitStr,
+}
+
+impl Parse for FileInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<kw::file>()?;
+        input.parse::<Token![=]>()?;
+        Ok(Self {
+            path: input.parse()?,
+        })
+    }
+}
+
+fn respan(stream: TokenStream2, span: Span) -> TokenStream2 {
+    stream
+        .into_iter()
+        .map(|tt| match tt {
+            TokenTree::Group(group) => {
+                let mut respanned = Group::new(group.delimiter(), respan(group.stream(), span));
+                respanned.set_span(span);
+                respanned.into()
+            }
+            mut tt => {
+                tt.set_span(span);
+                tt
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory holding `files`, standing in for a crate root.
+    fn crate_dir(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "lunch_core_include_{}_{}",
+            std::process::id(),
+            test
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        for (name, text) in files {
+            let path = dir.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        }
+
+        dir
+    }
+
+    fn resolve(loader: &mut Loader, input: &str) -> Result<String> {
+        loader
+            .resolve(TokenStream2::from_str(input).unwrap())
+            .map(|tokens| tokens.to_string())
+    }
+
+    fn messages(err: Error) -> Vec<String> {
+        err.into_iter().map(|err| err.to_string()).collect()
+    }
+
+    #[test]
+    fn include_splices_the_file_in_place() {
+        let dir = crate_dir(
+            "splice",
+            &[("families/risk.lunch", "family risk { signal a {} }")],
+        );
+        let mut loader = Loader::new().in_dir(dir);
+
+        let resolved = resolve(
+            &mut loader,
+            r#"component = demo { signals { include "families/risk.lunch" family format {} } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            resolved,
+            "component = demo { signals { family risk { signal a { } } family format { } } }"
+        );
+        assert_eq!(loader.files, ["families/risk.lunch"]);
+    }
+
+    #[test]
+    fn file_reads_the_whole_invocation_and_its_includes() {
+        let dir = crate_dir(
+            "file",
+            &[
+                (
+                    "rules/demo.lunch",
+                    r#"component = demo { include "more.lunch" }"#,
+                ),
+                ("more.lunch", "signals {}"),
+            ],
+        );
+        let mut loader = Loader::new().in_dir(dir);
+
+        let resolved = resolve(&mut loader, r#"file = "rules/demo.lunch""#).unwrap();
+
+        assert_eq!(resolved, "component = demo { signals { } }");
+        assert_eq!(loader.files, ["rules/demo.lunch", "more.lunch"]);
+    }
+
+    #[test]
+    fn missing_file_is_reported() {
+        let dir = crate_dir("missing", &[]);
+        let mut loader = Loader::new().in_dir(dir);
+
+        let err = resolve(&mut loader, r#"signals { include "gone.lunch" }"#).unwrap_err();
+
+        let messages = messages(err);
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].starts_with("cannot read `gone.lunch`: "));
+    }
+
+    #[test]
+    fn include_cycle_is_reported() {
+        let dir = crate_dir(
+            "cycle",
+            &[
+                ("a.lunch", r#"include "b.lunch""#),
+                ("b.lunch", r#"include "a.lunch""#),
+            ],
+        );
+        let mut loader = Loader::new().in_dir(dir);
+
+        let err = resolve(&mut loader, r#"signals { include "a.lunch" }"#).unwrap_err();
+
+        assert_eq!(messages(err), ["`a.lunch` includes itself"]);
+    }
+
+    #[test]
+    fn invalid_tokens_are_reported() {
+        let dir = crate_dir("invalid", &[("broken.lunch", "family risk {")]);
+        let mut loader = Loader::new().in_dir(dir);
+
+        let err = resolve(&mut loader, r#"signals { include "broken.lunch" }"#).unwrap_err();
+
+        assert_eq!(messages(err), ["`broken.lunch` is not valid token syntax"]);
+    }
+
+    #[test]
+    fn overlay_is_read_instead_of_disk() {
+        let dir = crate_dir("overlay", &[("risk.lunch", "family on_disk {}")]);
+        let path = dir.join("risk.lunch").canonicalize().unwrap();
+        let mut loader = Loader::new()
+            .in_dir(dir)
+            .with_overlay(HashMap::from([(path, String::from("family unsaved {}"))]));
+
+        let resolved = resolve(&mut loader, r#"signals { include "risk.lunch" }"#).unwrap();
+
+        assert_eq!(resolved, "signals { family unsaved { } }");
+    }
+
+    #[test]
+    fn include_is_only_spliced_where_an_item_starts() {
+        let dir = crate_dir("item_start", &[("a.lunch", "family a {}")]);
+        let mut loader = Loader::new().in_dir(dir);
+
+        let resolved = resolve(
+            &mut loader,
+            r#"signals { include "a.lunch" include "a.lunch" family b { signal c { when x == include "a.lunch" } } include "a.lunch" }
+               clinch { when signal.a.b { tag += include "a.lunch"; include "a.lunch" } }
+               suppress { signal.a.b when f(include "a.lunch") && [include "a.lunch"].is_empty() }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            resolved,
+            "signals { family a { } family a { } family b { signal c { when x == include \"a.lunch\" } } family a { } } \
+             clinch { when signal . a . b { tag += include \"a.lunch\" ; family a { } } } \
+             suppress { signal . a . b when f (include \"a.lunch\") && [include \"a.lunch\"] . is_empty () }"
+        );
+    }
+
+    #[test]
+    fn origin_names_the_file_a_token_was_read_from() {
+        let dir = crate_dir(
+            "origin",
+            &[
+                ("risk.lunch", r#"family risk {} include "more.lunch""#),
+                ("more.lunch", "family more {}"),
+            ],
+        );
+        let mut loader = Loader::new().in_dir(dir);
+
+        let tokens: Vec<_> = loader
+            .resolve(TokenStream2::from_str(r#"signals { include "risk.lunch" }"#).unwrap())
+            .unwrap()
+            .into_iter()
+            .collect();
+        let TokenTree::Group(block) = &tokens[1] else {
+            panic!("expected the signals block");
+        };
+        let spliced: Vec<_> = block.stream().into_iter().collect();
+
+        assert_eq!(loader.origin(tokens[0].span()), None);
+        assert_eq!(loader.origin(spliced[0].span()), Some("risk.lunch"));
+        // `family more`, read through risk.lunch
+        assert_eq!(loader.origin(spliced[3].span()), Some("risk.lunch"));
+    }
+
+    #[test]
+    fn locating_loader_maps_spans_back_to_the_file() {
+        let dir = crate_dir("locating", &[("risk.lunch", "\nfamily risk {}")]);
+        let mut loader = Loader::locating().in_dir(dir);
+
+        let tokens = loader
+            .resolve(TokenStream2::from_str(r#"include "risk.lunch""#).unwrap())
+            .unwrap();
+        let family = tokens.into_iter().next().unwrap();
+
+        assert_eq!(family.to_string(), "family");
+        assert_eq!(loader.file_of(family.span()), Some("risk.lunch"));
+        assert_eq!(
+            loader.location(family.span()).as_deref(),
+            Some("risk.lunch:2:1")
+        );
+    }
+}
@@ -0,0 +1,412 @@
+use crate::parse::clinch::{ClinchActionAst, ClinchBlock, ScoreOpAst, ScoreValueAst, TagValueAst};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use std::{collections::HashMap, fmt};
+use syn::{Lit, LitStr, Result};
+
+#[derive(Debug, Default)]
+pub struct ClinchIndex {
+    /// Clauses in declaration order. Order matters once `halt` is involved.
+    pub clauses: Vec<ClinchClauseInfo>,
+}
+
+impl ClinchIndex {
+    pub fn new() -> Self {
+        ClinchIndex {
+            clauses: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ClinchClauseInfo {
+    pub cfg: Vec<syn::Attribute>,
+    pub rule: Option<RuleInfo>,
+    pub signal: SignalId,
+    pub actions: Vec<ClinchAction>,
+    /// For an anonymous clause that shares its signal with other anonymous
+    /// clauses: its 1-based position among them.
+    pub anonymous_index: Option<usize>,
+}
+
+impl ClinchClauseInfo {
+    /// Name actions are attributed to: the rule ID, or the signal path for anonymous
+    /// clauses. Several anonymous clauses on one signal get `#1`, `#2`, ...
+    pub fn label(&self) -> String {
+        match (&self.rule, self.anonymous_index) {
+            (Some(rule), _) => rule.id.clone(),
+            (None, None) => format!("signal.{}.{}", self.signal.family, self.signal.name),
+            (None, Some(index)) => format!(
+                "signal.{}.{}#{}",
+                self.signal.family, self.signal.name, index
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RuleInfo {
+    pub id: String,
+    pub title: String,
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SignalId {
+    pub family: String,
+    pub name: String,
+}
+
+#[derive(Debug)]
+pub enum ClinchAction {
+    Emit {
+        event: String,
+        /// `field = <binding expr>` pairs captured when the emit fires.
+        payload: Vec<(String, TokenStream2)>,
+    },
+    Tag {
+        /// `tag format = ...` is pushed as `format:<value>`; `tag += ...` has no key.
+        key: Option<String>,
+        value: TagValue,
+    },
+    Score {
+        key: String,
+        operator: ScoreOp,
+        value: ScoreValue,
+    },
+    RunDeferred {
+        handler: String,
+    },
+    /// Run another component's pipeline on the same artifact and merge its
+    /// context under the component's name.
+    RunPipeline {
+        component: String,
+        /// `crate::pe_deep_mint` in `run pipeline crate::pe_deep_mint::pe_deep`.
+        module: Option<TokenStream2>,
+    },
+    Verdict {
+        level: VerdictLevel,
+    },
+    Halt,
+}
+
+#[derive(Debug)]
+pub enum TagValue {
+    /// `tag += "type:pdf"`
+    Literal(String),
+    /// `tag += "format:{format_probe.format}"`, lowered to `format!`.
+    Interpolated {
+        template: String,
+        args: Vec<TokenStream2>,
+    },
+    /// `tag format = format_probe.format`
+    Binding(TokenStream2),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ScoreValue {
+    Number(f64),
+    /// A `params` entry, read at runtime.
+    Param(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ScoreOp {
+    Add,
+    Sub,
+    Mul,
+    Set,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn from_ident(name: &str) -> Option<Self> {
+        match name {
+            "low" => Some(Severity::Low),
+            "medium" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum VerdictLevel {
+    Clean,
+    Suspicious,
+    Malicious,
+}
+
+impl VerdictLevel {
+    pub fn from_ident(name: &str) -> Option<Self> {
+        match name {
+            "clean" => Some(VerdictLevel::Clean),
+            "suspicious" => Some(VerdictLevel::Suspicious),
+            "malicious" => Some(VerdictLevel::Malicious),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerdictLevel::Clean => "clean",
+            VerdictLevel::Suspicious => "suspicious",
+            VerdictLevel::Malicious => "malicious",
+        }
+    }
+}
+
+impl ScoreOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScoreOp::Add => "+=",
+            ScoreOp::Sub => "-=",
+            ScoreOp::Mul => "*=",
+            ScoreOp::Set => "=",
+        }
+    }
+}
+
+/// DSL-shaped rendering, used for attribution records.
+impl fmt::Display for ClinchAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClinchAction::Emit { event, .. } => write!(f, "emit {}", event),
+            ClinchAction::Tag { key, value } => {
+                match key {
+                    Some(key) => write!(f, "tag {} = ", key)?,
+                    None => f.write_str("tag += ")?,
+                }
+                match value {
+                    TagValue::Literal(value) => write!(f, "{:?}", value),
+                    TagValue::Interpolated { template, args } => {
+                        let source = args.iter().fold(template.clone(), |acc, arg| {
+                            let arg = arg.to_string().replace(' ', "");
+                            acc.replacen("{}", &format!("{{{}}}", arg), 1)
+                        });
+                        write!(f, "{:?}", source)
+                    }
+                    TagValue::Binding(path) => write!(f, "{}", path.to_string().replace(' ', "")),
+                }
+            }
+            ClinchAction::Score {
+                key,
+                operator,
+                value,
+            } => match value {
+                ScoreValue::Number(value) => {
+                    write!(f, "score {} {} {:?}", key, operator.as_str(), value)
+                }
+                ScoreValue::Param(param) => {
+                    write!(f, "score {} {} {}", key, operator.as_str(), param)
+                }
+            },
+            ClinchAction::RunDeferred { handler } => write!(f, "run deferred {}", handler),
+            ClinchAction::RunPipeline {
+                component,
+                module: None,
+            } => write!(f, "run pipeline {}", component),
+            ClinchAction::RunPipeline {
+                component,
+                module: Some(module),
+            } => write!(f, "run pipeline {}::{}", module, component),
+            ClinchAction::Verdict { level } => write!(f, "verdict {}", level.as_str()),
+            ClinchAction::Halt => f.write_str("halt"),
+        }
+    }
+}
+
+pub fn build_clinch_index(clinch: &ClinchBlock) -> ClinchIndex {
+    let mut clauses = Vec::new();
+
+    for clause in &clinch.clauses {
+        let signal = SignalId {
+            family: clause.signal.family.to_string().clone(),
+            name: clause.signal.name.to_string().clone(),
+        };
+
+        let rule = clause.rule.as_ref().map(|rule| RuleInfo {
+            id: rule.id.to_string(),
+            title: rule.title.value(),
+            severity: Severity::from_ident(&rule.severity.to_string()).unwrap_or(Severity::Low),
+        });
+
+        let actions = index_actions(&clause.body.actions);
+        clauses.push(ClinchClauseInfo {
+            cfg: clause.attrs.clone(),
+            rule,
+            signal,
+            actions,
+            anonymous_index: None,
+        });
+    }
+
+    // number anonymous clauses that would otherwise share a label
+    let mut anonymous: HashMap<SignalId, Vec<usize>> = HashMap::new();
+    for (position, clause) in clauses.iter().enumerate() {
+        if clause.rule.is_none() {
+            anonymous
+                .entry(clause.signal.clone())
+                .or_default()
+                .push(position);
+        }
+    }
+    for positions in anonymous.values().filter(|positions| positions.len() > 1) {
+        for (index, &position) in positions.iter().enumerate() {
+            clauses[position].anonymous_index = Some(index + 1);
+        }
+    }
+
+    ClinchIndex { clauses }
+}
+
+/// Actions are already validated, so fallbacks here are unreachable.
+fn index_actions(actions: &[ClinchActionAst]) -> Vec<ClinchAction> {
+    let mut indexed = Vec::new();
+
+    for action in actions {
+        indexed.push(match action {
+            ClinchActionAst::Emit { event, payload } => ClinchAction::Emit {
+                event: event
+                    .segments
+                    .iter()
+                    .map(|segment| segment.ident.to_string())
+                    .collect::<Vec<_>>()
+                    .join("::"),
+                payload: payload
+                    .iter()
+                    .flat_map(|payload| &payload.fields)
+                    .map(|field| (field.name.to_string(), field.value.to_token_stream()))
+                    .collect(),
+            },
+            ClinchActionAst::Tag { key, value } => ClinchAction::Tag {
+                key: key.as_ref().map(|key| key.to_string()),
+                value: match value {
+                    TagValueAst::Literal(lit) => {
+                        parse_tag_template(lit).unwrap_or_else(|_| TagValue::Literal(lit.value()))
+                    }
+                    TagValueAst::Binding(path) => TagValue::Binding(path.to_token_stream()),
+                },
+            },
+            ClinchActionAst::Score { key, op, value } => ClinchAction::Score {
+                key: key.to_string(),
+                operator: match op {
+                    ScoreOpAst::Add => ScoreOp::Add,
+                    ScoreOpAst::Sub => ScoreOp::Sub,
+                    ScoreOpAst::Mul => ScoreOp::Mul,
+                    ScoreOpAst::Set => ScoreOp::Set,
+                },
+                value: score_value(value).unwrap_or(ScoreValue::Number(0.0)),
+            },
+            ClinchActionAst::RunDeferred { handler } => ClinchAction::RunDeferred {
+                handler: handler.to_string(),
+            },
+            ClinchActionAst::RunPipeline { module, component } => ClinchAction::RunPipeline {
+                component: component.to_string(),
+                module: module.as_ref().map(ToTokens::to_token_stream),
+            },
+            ClinchActionAst::Verdict { level } => ClinchAction::Verdict {
+                level: VerdictLevel::from_ident(&level.to_string()).unwrap_or(VerdictLevel::Clean),
+            },
+            ClinchActionAst::Halt => ClinchAction::Halt,
+        });
+    }
+
+    indexed
+}
+
+/// `None` for anything but a number literal or a param.
+pub fn score_value(value: &ScoreValueAst) -> Option<ScoreValue> {
+    match value {
+        ScoreValueAst::Param(param) => Some(ScoreValue::Param(param.to_string())),
+        ScoreValueAst::Literal { negative, lit } => {
+            let n: f64 = match lit {
+                Lit::Float(f) => f.base10_parse().ok()?,
+                Lit::Int(i) => i.base10_parse().ok()?,
+                _ => return None,
+            };
+            Some(ScoreValue::Number(if negative.is_some() { -n } else { n }))
+        }
+    }
+}
+
+/// Split a tag literal into a `format!` template and its `{binding.field}` arguments.
+/// `{{` and `}}` escape literal braces.
+pub fn parse_tag_template(lit: &LitStr) -> Result<TagValue> {
+    let source = lit.value();
+    let mut template = String::new();
+    let mut args = Vec::new();
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                template.push_str("{{");
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                template.push_str("}}");
+            }
+            '{' => {
+                let mut expr = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => expr.push(c),
+                        None => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "unclosed `{` in tag interpolation",
+                            ));
+                        }
+                    }
+                }
+
+                let arg: TokenStream2 = syn::parse_str::<syn::Expr>(&expr)
+                    .map_err(|_| {
+                        syn::Error::new_spanned(
+                            lit,
+                            format!("invalid tag interpolation `{{{}}}`", expr),
+                        )
+                    })?
+                    .into_token_stream();
+
+                template.push_str("{}");
+                args.push(arg);
+            }
+            '}' => {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    "unmatched `}` in tag value; use `}}` for a literal brace",
+                ));
+            }
+            c => template.push(c),
+        }
+    }
+
+    if args.is_empty() {
+        return Ok(TagValue::Literal(
+            template.replace("{{", "{").replace("}}", "}"),
+        ));
+    }
+
+    Ok(TagValue::Interpolated { template, args })
+}
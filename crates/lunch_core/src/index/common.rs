@@ -0,0 +1,8 @@
+#[derive(Debug)]
+pub struct StepInfo {
+    pub binding: String,
+    pub ops_fn: Option<String>, // e.g. "inspect_magic"
+    /// `do <step> output <name> children`: the output is a collection of child
+    /// `Artifact`s that get scanned recursively.
+    pub children: bool,
+}
@@ -1,7 +1,10 @@
-use std::collections::HashSet;
-
 pub mod clinch;
 pub mod common;
 pub mod operations;
+pub mod params;
 pub mod pipeline;
+pub mod profiles;
+pub mod rollup;
 pub mod signals;
+pub mod stages;
+pub mod suppress;
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+use syn::Result;
+
+use crate::{
+    index::common::StepInfo,
+    parse::{operations::OperationsBlock, stage::StageBlock},
+};
+
+#[derive(Debug)]
+pub struct OperationIndex {
+    pub index: HashMap<String, OperationInfo>,
+}
+
+#[derive(Debug)]
+pub struct OperationInfo {
+    pub steps: HashMap<String, StepInfo>,
+    /// Stage the operation is declared in; `None` for top-level `operations`.
+    pub stage: Option<String>,
+    /// Declared in a stage with a `when` gate; step results are `Option`s.
+    pub gated: bool,
+    /// `#[cfg(...)]` attributes, emitted on every lowered statement for this op.
+    pub cfg: Vec<syn::Attribute>,
+}
+
+pub fn build_operation_index(
+    operations_block: &OperationsBlock,
+    stage: Option<&StageBlock>,
+) -> Result<HashMap<String, OperationInfo>> {
+    let mut operations = HashMap::new();
+
+    for op in &operations_block.definitions {
+        let op_name = op.name.to_string();
+
+        let steps = op
+            .body
+            .stmts
+            .iter()
+            .map(|stmt| {
+                (
+                    stmt.step.to_string(),
+                    StepInfo {
+                        binding: stmt.output.to_string(),
+                        ops_fn: Some(stmt.step.to_string()),
+                        children: stmt.children.is_some(),
+                    },
+                )
+            })
+            .collect();
+
+        operations.insert(
+            op_name,
+            OperationInfo {
+                steps,
+                stage: stage.map(|stage| stage.name.to_string()),
+                gated: stage.is_some_and(|stage| stage.gate.is_some()),
+                cfg: op.attrs.clone(),
+            },
+        );
+    }
+
+    Ok(operations)
+}
@@ -0,0 +1,116 @@
+use crate::parse::params::{ParamDef, ParamsBlock};
+use syn::Lit;
+
+#[derive(Debug, Default)]
+pub struct ParamIndex {
+    pub params: Vec<ParamInfo>,
+}
+
+impl ParamIndex {
+    pub fn new() -> Self {
+        ParamIndex { params: Vec::new() }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ParamInfo> {
+        self.params.iter().find(|param| param.name == name)
+    }
+}
+
+#[derive(Debug)]
+pub struct ParamInfo {
+    pub name: String,
+    pub ty: ParamType,
+    pub value: ParamValue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamType {
+    F64,
+    I64,
+    U64,
+    Usize,
+    Bool,
+}
+
+impl ParamType {
+    pub fn from_ident(s: &str) -> Option<Self> {
+        match s {
+            "f64" => Some(ParamType::F64),
+            "i64" => Some(ParamType::I64),
+            "u64" => Some(ParamType::U64),
+            "usize" => Some(ParamType::Usize),
+            "bool" => Some(ParamType::Bool),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ParamType::F64 => "f64",
+            ParamType::I64 => "i64",
+            ParamType::U64 => "u64",
+            ParamType::Usize => "usize",
+            ParamType::Bool => "bool",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamValue {
+    Float(f64),
+    Int(i128),
+    Bool(bool),
+}
+
+impl ParamValue {
+    /// Value of a validated `params` entry; `None` when the literal doesn't fit
+    /// the declared type.
+    pub fn from_def(def: &ParamDef, ty: ParamType) -> Option<Self> {
+        let sign = if def.negative.is_some() { -1 } else { 1 };
+
+        match (ty, &def.value) {
+            (ParamType::Bool, Lit::Bool(b)) if def.negative.is_none() => {
+                Some(ParamValue::Bool(b.value))
+            }
+            (ParamType::F64, Lit::Float(f)) => f
+                .base10_parse::<f64>()
+                .ok()
+                .map(|v| ParamValue::Float(v * sign as f64)),
+            (ParamType::F64, Lit::Int(i)) => i
+                .base10_parse::<f64>()
+                .ok()
+                .map(|v| ParamValue::Float(v * sign as f64)),
+            (ParamType::I64, Lit::Int(i)) => i
+                .base10_parse::<i64>()
+                .ok()
+                .map(|v| ParamValue::Int(v as i128 * sign as i128)),
+            (ParamType::U64 | ParamType::Usize, Lit::Int(i)) if def.negative.is_none() => i
+                .base10_parse::<u64>()
+                .ok()
+                .map(|v| ParamValue::Int(v as i128)),
+            _ => None,
+        }
+    }
+}
+
+pub fn build_param_index(params: &ParamsBlock) -> ParamIndex {
+    let mut index = ParamIndex::new();
+
+    for def in &params.params {
+        // validated already
+        let Some(ty) = ParamType::from_ident(&def.ty.to_string()) else {
+            continue;
+        };
+        let Some(value) = ParamValue::from_def(def, ty) else {
+            continue;
+        };
+
+        index.params.push(ParamInfo {
+            name: def.name.to_string(),
+            ty,
+            value,
+        });
+    }
+
+    index
+}
@@ -0,0 +1,149 @@
+use proc_macro2::Span;
+use std::collections::HashMap;
+use syn::{Error, Result};
+
+use crate::{
+    diagnostics::Diagnostics,
+    index::{
+        clinch::{self, ClinchIndex, build_clinch_index},
+        common::StepInfo,
+        operations::{OperationIndex, build_operation_index},
+        params::{ParamIndex, build_param_index},
+        profiles::{ProfileIndex, build_profile_index},
+        rollup::{RollupIndex, build_rollup_index},
+        signals::{SignalIndex, build_signal_index},
+        stages::{StageIndex, build_stage_index},
+        suppress::{SuppressIndex, build_suppress_index},
+    },
+    parse::pipeline::PipelineAst,
+};
+
+#[derive(Debug)]
+pub struct PipelineIndex {
+    pub param_index: ParamIndex,
+    pub operation_index: OperationIndex,
+    pub rollup_index: RollupIndex,
+    pub signal_index: SignalIndex,
+    pub stage_index: StageIndex,
+    pub suppress_index: SuppressIndex,
+    pub clinch_index: ClinchIndex,
+    pub profile_index: ProfileIndex,
+}
+
+/*
+pub struct PipelineIndex {
+    pub meta: PipelineMeta,
+    pub operations: HashMap<…>,
+    pub signals: HashMap<…>,
+    pub clinch: ClinchInfo,
+}
+
+pub struct PipelineMeta {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub version: Option<String>,
+    pub scope: Option<String>,
+    pub raw: HashMap<String, String>, // future-proof
+}
+
+*/
+
+impl PipelineIndex {
+    pub fn from_operations(ast: &PipelineAst) -> Result<Self> {
+        if ast.operation_blocks().next().is_none() {
+            return Err(Error::new(
+                Span::call_site(),
+                "missing `operations` block in pipeline definition",
+            ));
+        } // no ops is allowed (for now)
+
+        let mut operation_infos = HashMap::new();
+        for (stage, ops) in ast.operation_blocks() {
+            operation_infos.extend(build_operation_index(ops, stage)?);
+        }
+
+        Ok(Self {
+            param_index: ParamIndex::new(),
+            operation_index: OperationIndex {
+                index: operation_infos,
+            },
+            rollup_index: RollupIndex::new(),
+            signal_index: SignalIndex::new(),
+            stage_index: StageIndex::new(),
+            suppress_index: SuppressIndex::new(),
+            clinch_index: ClinchIndex::new(),
+            profile_index: ProfileIndex::new(),
+        })
+    }
+
+    pub fn extend_with_params(&mut self, ast: &PipelineAst) {
+        // `params` is optional
+        if let Some(params_block) = &ast.params {
+            self.param_index = build_param_index(params_block);
+        }
+    }
+
+    pub fn extend_with_rollup(&mut self, ast: &PipelineAst) {
+        // `rollup` is optional
+        if let Some(rollup_block) = &ast.rollup {
+            self.rollup_index = build_rollup_index(rollup_block);
+        }
+    }
+
+    pub fn extend_with_signals(&mut self, ast: &PipelineAst) -> Result<()> {
+        if ast.signal_blocks().next().is_none() {
+            return Err(Error::new(
+                Span::call_site(),
+                "missing `signals` block in pipeline definition",
+            ));
+        }
+
+        for (stage, signals_block) in ast.signal_blocks() {
+            let families = build_signal_index(signals_block, stage);
+            self.signal_index.families.extend(families);
+        }
+
+        Ok(())
+    }
+
+    pub fn extend_with_stages(&mut self, ast: &PipelineAst, diags: &mut Diagnostics) {
+        self.stage_index = build_stage_index(ast, &self.operation_index, &self.signal_index, diags);
+    }
+
+    pub fn extend_with_suppress(&mut self, ast: &PipelineAst) {
+        // `suppress` is optional
+        if let Some(suppress_block) = &ast.suppress {
+            self.suppress_index = build_suppress_index(suppress_block);
+        }
+    }
+
+    pub fn extend_with_clinch(&mut self, ast: &PipelineAst) -> Result<()> {
+        let clinch_block = match &ast.clinch {
+            Some(c) => c,
+            None => {
+                return Err(Error::new(
+                    Span::call_site(),
+                    "missing `clinch` block in pipeline definition",
+                ));
+            }
+        };
+
+        let clinch_index: clinch::ClinchIndex = build_clinch_index(clinch_block);
+
+        self.clinch_index.clauses = clinch_index.clauses;
+
+        Ok(())
+    }
+
+    pub fn extend_with_profiles(&mut self, ast: &PipelineAst) {
+        // profiles are optional
+        self.profile_index = build_profile_index(&ast.profiles);
+    }
+
+    pub fn get_step(&self, op: &str, step: &str) -> Option<&StepInfo> {
+        self.operation_index
+            .index
+            .get(op)
+            .and_then(|op| op.steps.get(step))
+    }
+}
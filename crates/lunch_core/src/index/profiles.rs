@@ -0,0 +1,69 @@
+use crate::{
+    index::clinch::{ScoreValue, SignalId, score_value},
+    parse::profile::ProfileBlock,
+};
+
+#[derive(Debug, Default)]
+pub struct ProfileIndex {
+    /// Profiles in declaration order.
+    pub profiles: Vec<ProfileInfo>,
+}
+
+impl ProfileIndex {
+    pub fn new() -> Self {
+        ProfileIndex {
+            profiles: Vec::new(),
+        }
+    }
+
+    /// `(profile, value)` for every profile that overrides `score <key>` on `signal`.
+    pub fn overrides_for<'a>(
+        &'a self,
+        signal: &'a SignalId,
+        key: &'a str,
+    ) -> impl Iterator<Item = (&'a str, &'a ScoreValue)> + 'a {
+        self.profiles.iter().flat_map(move |profile| {
+            profile
+                .overrides
+                .iter()
+                .filter(move |o| &o.signal == signal && o.key == key)
+                .map(move |o| (profile.name.as_str(), &o.value))
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ProfileInfo {
+    pub name: String,
+    pub overrides: Vec<ScoreOverrideInfo>,
+}
+
+#[derive(Debug)]
+pub struct ScoreOverrideInfo {
+    pub key: String,
+    pub signal: SignalId,
+    pub value: ScoreValue,
+}
+
+pub fn build_profile_index(profiles: &[ProfileBlock]) -> ProfileIndex {
+    let profiles = profiles
+        .iter()
+        .map(|profile| ProfileInfo {
+            name: profile.name.to_string(),
+            overrides: profile
+                .overrides
+                .iter()
+                .map(|o| ScoreOverrideInfo {
+                    key: o.key.to_string(),
+                    signal: SignalId {
+                        family: o.signal.family.to_string(),
+                        name: o.signal.name.to_string(),
+                    },
+                    value: score_value(&o.value).unwrap_or(ScoreValue::Number(0.0)),
+                })
+                .collect(),
+        })
+        .collect();
+
+    ProfileIndex { profiles }
+}
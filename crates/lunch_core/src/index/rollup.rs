@@ -0,0 +1,69 @@
+use crate::parse::rollup::{RollupBlock, RollupRule};
+
+#[derive(Debug, Default)]
+pub struct RollupIndex {
+    pub rules: Vec<RollupInfo>,
+}
+
+impl RollupIndex {
+    pub fn new() -> Self {
+        RollupIndex { rules: Vec::new() }
+    }
+}
+
+#[derive(Debug)]
+pub enum RollupInfo {
+    Score { key: String, func: AggregateFn },
+    Tags { prefix: String },
+    Verdict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateFn {
+    Max,
+    Min,
+    Sum,
+}
+
+impl AggregateFn {
+    pub fn from_ident(s: &str) -> Option<Self> {
+        match s {
+            "max" => Some(AggregateFn::Max),
+            "min" => Some(AggregateFn::Min),
+            "sum" => Some(AggregateFn::Sum),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AggregateFn::Max => "max",
+            AggregateFn::Min => "min",
+            AggregateFn::Sum => "sum",
+        }
+    }
+}
+
+pub fn build_rollup_index(rollup: &RollupBlock) -> RollupIndex {
+    let mut index = RollupIndex::new();
+
+    for entry in &rollup.entries {
+        let target = entry.target.to_string();
+
+        let info = match &entry.rule {
+            RollupRule::Union { prefix, .. } => RollupInfo::Tags {
+                prefix: prefix.as_ref().map(|p| p.value()).unwrap_or_default(),
+            },
+            RollupRule::Aggregate { .. } if target == "verdict" => RollupInfo::Verdict,
+            RollupRule::Aggregate { func, .. } => RollupInfo::Score {
+                key: target,
+                // validated already
+                func: AggregateFn::from_ident(&func.to_string()).unwrap_or(AggregateFn::Max),
+            },
+        };
+
+        index.rules.push(info);
+    }
+
+    index
+}
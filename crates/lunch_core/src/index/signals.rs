@@ -1,12 +1,9 @@
 use proc_macro2::TokenStream as TokenStream2;
-use proc_macro2::TokenTree;
-use serde_json::to_string;
 use std::collections::HashMap;
-use syn::{Error, Result};
 
-use crate::lunch::parse::signals::SignalsBlock;
+use crate::parse::{signals::SignalsBlock, stage::StageBlock};
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct SignalIndex {
     pub families: HashMap<String, FamilyInfo>,
 }
@@ -25,6 +22,10 @@ pub struct FamilyInfo {
 
 #[derive(Debug)]
 pub struct SignalInfo {
+    /// Stage the signal is declared in; `None` for top-level `signals`.
+    pub stage: Option<String>,
+    /// `#[cfg(...)]` from the family and the signal itself.
+    pub cfg: Vec<syn::Attribute>,
     pub derives_from: DeriveFrom,
     pub when: TokenStream2,
 }
@@ -35,7 +36,10 @@ pub struct DeriveFrom {
     pub step: String,
 }
 
-pub fn build_signal_index(signals_block: &SignalsBlock) -> Result<HashMap<String, FamilyInfo>> {
+pub fn build_signal_index(
+    signals_block: &SignalsBlock,
+    stage: Option<&StageBlock>,
+) -> HashMap<String, FamilyInfo> {
     let mut families: HashMap<String, FamilyInfo> = HashMap::new();
 
     for family in &signals_block.families {
@@ -51,18 +55,14 @@ pub fn build_signal_index(signals_block: &SignalsBlock) -> Result<HashMap<String
             let signal_name = signal.name.to_string();
 
             if family_entry.signals.contains_key(&signal_name) {
-                return Err(Error::new_spanned(
-                    &signal.name,
-                    format!(
-                        "duplicate signal `{}` in family `{}`",
-                        signal_name, family_name
-                    ),
-                ));
+                continue; // reported by signal validation
             }
 
             family_entry.signals.insert(
                 signal_name,
                 SignalInfo {
+                    stage: stage.map(|stage| stage.name.to_string()),
+                    cfg: family.attrs.iter().chain(&signal.attrs).cloned().collect(),
                     derives_from: DeriveFrom {
                         operation: signal.body.derive_from.operation.to_string(),
                         step: signal.body.derive_from.step.to_string(),
@@ -73,5 +73,5 @@ pub fn build_signal_index(signals_block: &SignalsBlock) -> Result<HashMap<String
         }
     }
 
-    Ok(families)
+    families
 }
@@ -0,0 +1,121 @@
+/*
+
+Stage ordering. Top-level `operations`/`signals` run first, then each `stage` in
+declaration order. A stage may only look backwards: its gate must be a signal from
+an earlier stage, and its signals may only derive from ops that have already run.
+
+*/
+
+use std::collections::HashMap;
+use syn::Error;
+
+use crate::{
+    diagnostics::Diagnostics,
+    index::{clinch::SignalId, operations::OperationIndex, signals::SignalIndex},
+    parse::pipeline::PipelineAst,
+    validate::{clinch::resolve_signal, core_validate::validate_unique_names},
+};
+
+#[derive(Debug, Default)]
+pub struct StageIndex {
+    pub stages: Vec<StageInfo>,
+}
+
+impl StageIndex {
+    pub fn new() -> Self {
+        StageIndex { stages: Vec::new() }
+    }
+}
+
+#[derive(Debug)]
+pub struct StageInfo {
+    pub name: String,
+    pub gate: Option<SignalId>,
+}
+
+pub fn build_stage_index(
+    ast: &PipelineAst,
+    op_index: &OperationIndex,
+    signal_index: &SignalIndex,
+    diags: &mut Diagnostics,
+) -> StageIndex {
+    diags.check(validate_unique_names(
+        ast.stages.iter().map(|stage| stage.name.clone()),
+        "stage",
+    ));
+
+    // 0 is the top level; stages count up from 1.
+    let order: HashMap<String, usize> = ast
+        .stages
+        .iter()
+        .enumerate()
+        .map(|(i, stage)| (stage.name.to_string(), i + 1))
+        .collect();
+    let position = |stage: &Option<String>| stage.as_ref().map_or(0, |name| order[name]);
+    let describe = |stage: &Option<String>| match stage {
+        Some(name) => format!("stage `{}`", name),
+        None => "the top level".to_string(),
+    };
+
+    let mut index = StageIndex::new();
+
+    for (i, stage) in ast.stages.iter().enumerate() {
+        let gate = match &stage.gate {
+            Some(gate) => {
+                // The gate is kept either way so lowering never sees a half-built stage list.
+                if let Some(signal) = diags.check(resolve_signal(gate, signal_index))
+                    && position(&signal.stage) > i
+                {
+                    diags.push(Error::new_spanned(
+                        &gate.name,
+                        format!(
+                            "stage `{}` is gated on `signal.{}.{}`, which is declared in {} and has not run yet",
+                            stage.name,
+                            gate.family,
+                            gate.name,
+                            describe(&signal.stage)
+                        ),
+                    ));
+                }
+
+                Some(SignalId {
+                    family: gate.family.to_string(),
+                    name: gate.name.to_string(),
+                })
+            }
+            None => None,
+        };
+
+        index.stages.push(StageInfo {
+            name: stage.name.to_string(),
+            gate,
+        });
+    }
+
+    for (stage, signals_block) in ast.signal_blocks() {
+        let stage_name = stage.map(|stage| stage.name.to_string());
+
+        for signal in signals_block.families.iter().flat_map(|f| &f.signals) {
+            let derive_from = &signal.body.derive_from;
+
+            let Some(op) = op_index.index.get(&derive_from.operation.to_string()) else {
+                continue; // reported by signal validation
+            };
+
+            if position(&op.stage) > position(&stage_name) {
+                diags.push(Error::new_spanned(
+                    &derive_from.operation,
+                    format!(
+                        "signal `{}` in {} derives from operation `{}`, which is declared in later {}",
+                        signal.name,
+                        describe(&stage_name),
+                        derive_from.operation,
+                        describe(&op.stage)
+                    ),
+                ));
+            }
+        }
+    }
+
+    index
+}
@@ -0,0 +1,65 @@
+use proc_macro2::TokenStream as TokenStream2;
+
+use crate::{
+    index::clinch::SignalId,
+    parse::suppress::{SuppressBlock, SuppressEntry},
+};
+
+#[derive(Debug, Default)]
+pub struct SuppressIndex {
+    pub signals: Vec<SignalSuppression>,
+    pub tags: Vec<TagSuppression>,
+}
+
+impl SuppressIndex {
+    pub fn new() -> Self {
+        SuppressIndex {
+            signals: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SignalSuppression {
+    pub signal: SignalId,
+    pub when: TokenStream2,
+}
+
+#[derive(Debug)]
+pub struct TagSuppression {
+    /// Full tag as pushed by clinch, e.g. `type:pe`.
+    pub tag: String,
+    /// `Artifact::meta` key compared against the allowlist, e.g. `hash`.
+    pub meta_key: String,
+    /// Allowlist path relative to the component crate's `CARGO_MANIFEST_DIR`.
+    pub list: String,
+}
+
+pub fn build_suppress_index(suppress: &SuppressBlock) -> SuppressIndex {
+    let mut index = SuppressIndex::new();
+
+    for entry in &suppress.entries {
+        match entry {
+            SuppressEntry::Signal { signal, when } => index.signals.push(SignalSuppression {
+                signal: SignalId {
+                    family: signal.family.to_string(),
+                    name: signal.name.to_string(),
+                },
+                when: when.clone(),
+            }),
+            SuppressEntry::Tag {
+                key,
+                value,
+                meta_key,
+                list,
+            } => index.tags.push(TagSuppression {
+                tag: format!("{}:{}", key, value.value()),
+                meta_key: meta_key.to_string(),
+                list: list.value(),
+            }),
+        }
+    }
+
+    index
+}
@@ -0,0 +1,26 @@
+/*
+
+Front end of the lunch DSL: parsing, validation, indexing and lints. It works on
+proc-macro2 tokens and never touches `proc_macro`, so anything can use it: the
+`lunch!` macro in lucius_macro, CLI linters, formatters, manifest exporters.
+
+`syn::parse2::<LunchGenInput>` gives a `PipelineAst`, and its
+`validate_and_index` gives a `PipelineIndex`, and `lints::lint_pipeline` the
+warnings. Outside a proc macro, proc-macro2 runs on its fallback lexer, so
+`Span::start()` has real lines and columns.
+
+*/
+
+pub mod diagnostics;
+pub mod include;
+pub mod index;
+pub mod lints;
+pub mod manifest;
+pub mod parse;
+pub mod validate;
+
+#[cfg(test)]
+mod testing;
+
+pub use index::pipeline::PipelineIndex;
+pub use parse::pipeline::{LunchGenInput, PipelineAst};
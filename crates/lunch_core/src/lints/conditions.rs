@@ -0,0 +1,507 @@
+/*
+
+Static reading of signal `when` conditions. A condition is split on `&&` into
+atoms. An atom that compares a place on the step's binding (`probe.field`,
+`probe.bytes[0]`) with a literal is understood. Anything else is kept as its
+tokens and only ever matches itself. That is enough to catch conditions that
+contradict themselves and signals that repeat or narrow each other, without
+pretending to be a solver.
+
+*/
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::ToTokens;
+use std::cmp::Ordering;
+use syn::{BinOp, Expr, Lit, Member, UnOp};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    Char(char),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    /// `None` when the two can't be ordered, e.g. an int against a string.
+    fn order(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
+
+    fn same_kind(&self, other: &Value) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    fn from_op(op: &BinOp) -> Option<Self> {
+        Some(match op {
+            BinOp::Eq(_) => Cmp::Eq,
+            BinOp::Ne(_) => Cmp::Ne,
+            BinOp::Lt(_) => Cmp::Lt,
+            BinOp::Le(_) => Cmp::Le,
+            BinOp::Gt(_) => Cmp::Gt,
+            BinOp::Ge(_) => Cmp::Ge,
+            _ => return None,
+        })
+    }
+
+    /// `lit < place` is `place > lit`.
+    fn flip(self) -> Self {
+        match self {
+            Cmp::Lt => Cmp::Gt,
+            Cmp::Le => Cmp::Ge,
+            Cmp::Gt => Cmp::Lt,
+            Cmp::Ge => Cmp::Le,
+            cmp => cmp,
+        }
+    }
+
+    /// Whether `a <cmp> b` holds; `None` when the values can't be compared.
+    fn holds(self, a: &Value, b: &Value) -> Option<bool> {
+        if !a.same_kind(b) {
+            return None;
+        }
+
+        match self {
+            Cmp::Eq => Some(a == b),
+            Cmp::Ne => Some(a != b),
+            Cmp::Lt => a.order(b).map(Ordering::is_lt),
+            Cmp::Le => a.order(b).map(Ordering::is_le),
+            Cmp::Gt => a.order(b).map(Ordering::is_gt),
+            Cmp::Ge => a.order(b).map(Ordering::is_ge),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Atom {
+    Compare {
+        place: String,
+        cmp: Cmp,
+        value: Value,
+    },
+    Opaque(String),
+}
+
+/// One end of the range a place is held to; `inclusive` is `<=`/`>=`.
+#[derive(Debug, Clone)]
+struct Bound {
+    value: Value,
+    inclusive: bool,
+}
+
+#[derive(Debug, Default)]
+struct Range {
+    lower: Option<Bound>,
+    upper: Option<Bound>,
+}
+
+impl Range {
+    fn tighten_lower(&mut self, bound: Bound) {
+        let tighter = match &self.lower {
+            None => true,
+            Some(lower) => match bound.value.order(&lower.value) {
+                Some(Ordering::Greater) => true,
+                Some(Ordering::Equal) => !bound.inclusive,
+                _ => false,
+            },
+        };
+        if tighter {
+            self.lower = Some(bound);
+        }
+    }
+
+    fn tighten_upper(&mut self, bound: Bound) {
+        let tighter = match &self.upper {
+            None => true,
+            Some(upper) => match bound.value.order(&upper.value) {
+                Some(Ordering::Less) => true,
+                Some(Ordering::Equal) => !bound.inclusive,
+                _ => false,
+            },
+        };
+        if tighter {
+            self.upper = Some(bound);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        let (Some(lower), Some(upper)) = (&self.lower, &self.upper) else {
+            return false;
+        };
+
+        match lower.value.order(&upper.value) {
+            Some(Ordering::Greater) => true,
+            Some(Ordering::Equal) => !(lower.inclusive && upper.inclusive),
+            _ => false,
+        }
+    }
+
+    /// Whether every value in the range satisfies `<cmp> value`.
+    fn implies(&self, cmp: Cmp, value: &Value) -> bool {
+        let above = |bound: &Option<Bound>, strict: bool| {
+            bound.as_ref().is_some_and(|b| match b.value.order(value) {
+                Some(Ordering::Greater) => true,
+                Some(Ordering::Equal) => !strict || !b.inclusive,
+                _ => false,
+            })
+        };
+        let below = |bound: &Option<Bound>, strict: bool| {
+            bound.as_ref().is_some_and(|b| match b.value.order(value) {
+                Some(Ordering::Less) => true,
+                Some(Ordering::Equal) => !strict || !b.inclusive,
+                _ => false,
+            })
+        };
+
+        match cmp {
+            Cmp::Gt => above(&self.lower, true),
+            Cmp::Ge => above(&self.lower, false),
+            Cmp::Lt => below(&self.upper, true),
+            Cmp::Le => below(&self.upper, false),
+            Cmp::Ne => above(&self.lower, true) || below(&self.upper, true),
+            Cmp::Eq => matches!(
+                (&self.lower, &self.upper),
+                (Some(lower), Some(upper))
+                    if lower.inclusive && upper.inclusive && lower.value == *value && upper.value == *value
+            ),
+        }
+    }
+}
+
+pub struct Condition {
+    atoms: Vec<Atom>,
+}
+
+impl Condition {
+    /// `binding` is the output of the step the signal derives from; places are
+    /// only recognised on it.
+    pub fn parse(when: &TokenStream2, binding: &str) -> Self {
+        let mut atoms = Vec::new();
+
+        match syn::parse2::<Expr>(when.clone()) {
+            Ok(expr) => {
+                let mut conjuncts = Vec::new();
+                split_and(&expr, &mut conjuncts);
+                for conjunct in conjuncts {
+                    atoms.extend(atoms_of(conjunct, binding));
+                }
+            }
+            Err(_) => atoms.push(Atom::Opaque(when.to_string())),
+        }
+
+        Self { atoms }
+    }
+
+    /// The first place whose comparisons can't all hold at once.
+    pub fn contradiction(&self) -> Option<&str> {
+        let mut places: Vec<&str> = self
+            .atoms
+            .iter()
+            .filter_map(|atom| match atom {
+                Atom::Compare { place, .. } => Some(place.as_str()),
+                Atom::Opaque(_) => None,
+            })
+            .collect();
+        places.dedup();
+
+        places.into_iter().find(|place| !self.satisfiable_at(place))
+    }
+
+    /// Whether every value that satisfies `self` also satisfies `other`.
+    pub fn implies(&self, other: &Condition) -> bool {
+        other.atoms.iter().all(|atom| self.implies_atom(atom))
+    }
+
+    fn compares_at<'a>(&'a self, place: &'a str) -> impl Iterator<Item = (Cmp, &'a Value)> {
+        self.atoms.iter().filter_map(move |atom| match atom {
+            Atom::Compare {
+                place: p,
+                cmp,
+                value,
+            } if p == place => Some((*cmp, value)),
+            _ => None,
+        })
+    }
+
+    fn equal_at<'a>(&'a self, place: &'a str) -> Option<&'a Value> {
+        self.compares_at(place)
+            .find(|(cmp, _)| *cmp == Cmp::Eq)
+            .map(|(_, value)| value)
+    }
+
+    fn range_at(&self, place: &str) -> Range {
+        let mut range = Range::default();
+
+        for (cmp, value) in self.compares_at(place) {
+            // On integers `x > 3` is `x >= 4`, so `x > 3 && x < 4` comes out empty.
+            let (value, inclusive) = match (cmp, value) {
+                (Cmp::Gt, Value::Int(n)) => (Value::Int(n + 1), true),
+                (Cmp::Lt, Value::Int(n)) => (Value::Int(n - 1), true),
+                (Cmp::Gt | Cmp::Lt, value) => (value.clone(), false),
+                (_, value) => (value.clone(), true),
+            };
+
+            match cmp {
+                Cmp::Gt | Cmp::Ge => range.tighten_lower(Bound { value, inclusive }),
+                Cmp::Lt | Cmp::Le => range.tighten_upper(Bound { value, inclusive }),
+                Cmp::Eq | Cmp::Ne => {}
+            }
+        }
+
+        range
+    }
+
+    fn satisfiable_at(&self, place: &str) -> bool {
+        if let Some(value) = self.equal_at(place) {
+            return self
+                .compares_at(place)
+                .all(|(cmp, other)| cmp.holds(value, other) != Some(false));
+        }
+
+        let range = self.range_at(place);
+        if range.is_empty() {
+            return false;
+        }
+
+        // `x >= 3 && x <= 3 && x != 3`
+        !self
+            .compares_at(place)
+            .any(|(cmp, value)| cmp == Cmp::Ne && range.implies(Cmp::Eq, value))
+    }
+
+    fn implies_atom(&self, atom: &Atom) -> bool {
+        if self.atoms.contains(atom) {
+            return true;
+        }
+
+        let Atom::Compare { place, cmp, value } = atom else {
+            return false;
+        };
+
+        match self.equal_at(place) {
+            Some(known) => cmp.holds(known, value) == Some(true),
+            None => self.range_at(place).implies(*cmp, value),
+        }
+    }
+}
+
+fn split_and<'a>(expr: &'a Expr, out: &mut Vec<&'a Expr>) {
+    match unwrap(expr) {
+        Expr::Binary(binary) if matches!(binary.op, BinOp::And(_)) => {
+            split_and(&binary.left, out);
+            split_and(&binary.right, out);
+        }
+        expr => out.push(expr),
+    }
+}
+
+fn unwrap(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Paren(paren) => unwrap(&paren.expr),
+        Expr::Group(group) => unwrap(&group.expr),
+        expr => expr,
+    }
+}
+
+fn atoms_of(expr: &Expr, binding: &str) -> Vec<Atom> {
+    let compare = |place: String, cmp: Cmp, value: Value| {
+        let mut atoms = Vec::new();
+
+        // `magic == [0x4D, 0x5A]` also pins `magic[0]` and `magic[1]`.
+        if let (Cmp::Eq, Value::Array(items)) = (cmp, &value) {
+            for (i, item) in items.iter().enumerate() {
+                atoms.push(Atom::Compare {
+                    place: format!("{}[{}]", place, i),
+                    cmp: Cmp::Eq,
+                    value: item.clone(),
+                });
+            }
+        }
+
+        atoms.push(Atom::Compare { place, cmp, value });
+        atoms
+    };
+
+    match unwrap(expr) {
+        Expr::Binary(binary) => {
+            if let Some(cmp) = Cmp::from_op(&binary.op) {
+                if let (Some(place), Some(value)) =
+                    (place_of(&binary.left, binding), literal(&binary.right))
+                {
+                    return compare(place, cmp, value);
+                }
+                if let (Some(value), Some(place)) =
+                    (literal(&binary.left), place_of(&binary.right, binding))
+                {
+                    return compare(place, cmp.flip(), value);
+                }
+            }
+        }
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Not(_)) => {
+            if let Some(place) = place_of(&unary.expr, binding) {
+                return compare(place, Cmp::Eq, Value::Bool(false));
+            }
+        }
+        expr => {
+            if let Some(place) = place_of(expr, binding) {
+                return compare(place, Cmp::Eq, Value::Bool(true));
+            }
+        }
+    }
+
+    vec![Atom::Opaque(expr.to_token_stream().to_string())]
+}
+
+/// `binding`, `binding.field`, `binding.field[3]` and so on, rendered as text.
+fn place_of(expr: &Expr, binding: &str) -> Option<String> {
+    match unwrap(expr) {
+        Expr::Path(path) if path.qself.is_none() && path.path.is_ident(binding) => {
+            Some(binding.to_string())
+        }
+        Expr::Field(field) => {
+            let base = place_of(&field.base, binding)?;
+            Some(match &field.member {
+                Member::Named(name) => format!("{}.{}", base, name),
+                Member::Unnamed(index) => format!("{}.{}", base, index.index),
+            })
+        }
+        Expr::Index(index) => {
+            let base = place_of(&index.expr, binding)?;
+            match literal(&index.index)? {
+                Value::Int(i) => Some(format!("{}[{}]", base, i)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn literal(expr: &Expr) -> Option<Value> {
+    match unwrap(expr) {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(int) => int.base10_parse().ok().map(Value::Int),
+            Lit::Float(float) => float.base10_parse().ok().map(Value::Float),
+            Lit::Bool(b) => Some(Value::Bool(b.value)),
+            Lit::Str(s) => Some(Value::Str(s.value())),
+            Lit::Char(c) => Some(Value::Char(c.value())),
+            Lit::Byte(b) => Some(Value::Int(b.value().into())),
+            _ => None,
+        },
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => match literal(&unary.expr)? {
+            Value::Int(n) => Some(Value::Int(-n)),
+            Value::Float(n) => Some(Value::Float(-n)),
+            _ => None,
+        },
+        Expr::Array(array) => array
+            .elems
+            .iter()
+            .map(literal)
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(when: &str) -> Condition {
+        Condition::parse(&when.parse().unwrap(), "probe")
+    }
+
+    #[test]
+    fn contradicting_ranges_are_unsatisfiable() {
+        let when = condition("probe.entropy > 7.5 && probe.entropy < 2.0");
+
+        assert_eq!(when.contradiction(), Some("probe.entropy"));
+    }
+
+    #[test]
+    fn array_equality_fixes_its_elements() {
+        let when = condition("probe.magic == [0x25, 0x50, 0x44, 0x46] && probe.magic[0] == 0x4D");
+
+        assert_eq!(when.contradiction(), Some("probe.magic[0]"));
+    }
+
+    #[test]
+    fn bare_bool_contradicts_its_negation() {
+        let when = condition("probe.matched && !probe.matched");
+
+        assert_eq!(when.contradiction(), Some("probe.matched"));
+    }
+
+    #[test]
+    fn overlapping_ranges_are_satisfiable() {
+        let when = condition("probe.entropy > 2.0 && probe.entropy < 7.5");
+
+        assert_eq!(when.contradiction(), None);
+    }
+
+    #[test]
+    fn reordered_conditions_imply_each_other() {
+        let a = condition("probe.magic[0] == 0x4D && probe.magic[1] == 0x5A");
+        let b = condition("probe.magic[1] == 0x5A && (probe.magic[0] == 0x4D)");
+
+        assert!(a.implies(&b));
+        assert!(b.implies(&a));
+    }
+
+    #[test]
+    fn stricter_condition_implies_weaker() {
+        let strict = condition("probe.entropy > 7.5");
+        let loose = condition("probe.entropy >= 7.0");
+
+        assert!(strict.implies(&loose));
+        assert!(!loose.implies(&strict));
+    }
+
+    #[test]
+    fn extra_conjunct_implies_the_rest() {
+        let escalated = condition("probe.matched && probe.magic[0] == 0x4D");
+        let base = condition("probe.magic[0] == 0x4D");
+
+        assert!(escalated.implies(&base));
+        assert!(!base.implies(&escalated));
+    }
+
+    #[test]
+    fn opaque_parts_only_match_themselves() {
+        let a = condition("probe.entropy > HIGH_ENTROPY");
+        let b = condition("probe.entropy > LOW_ENTROPY");
+
+        assert!(a.implies(&condition("probe.entropy > HIGH_ENTROPY")));
+        assert!(!a.implies(&b));
+        assert!(!b.implies(&a));
+    }
+
+    #[test]
+    fn places_are_only_read_on_the_binding() {
+        let a = condition("other.entropy > 7.5");
+        let b = condition("other.entropy < 2.0");
+
+        assert_eq!(
+            condition("other.entropy > 7.5 && other.entropy < 2.0").contradiction(),
+            None
+        );
+        assert!(!a.implies(&b));
+    }
+}
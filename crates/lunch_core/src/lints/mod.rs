@@ -0,0 +1,611 @@
+/*
+
+Lint pass. Runs after validation, over the AST (for spans) and the finished
+index, and flags rules that are declared but can never matter. Findings become
+compiler warnings through lucius_macro's `lowering::lints`. `#[allow(lunch::<lint>)]` on an
+item silences it; on an operation or family it covers what's inside.
+
+*/
+
+mod conditions;
+
+use proc_macro2::Span;
+use quote::ToTokens;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use syn::spanned::Spanned;
+
+use crate::{
+    index::{
+        clinch::{ClinchAction, ScoreOp, ScoreValue},
+        pipeline::PipelineIndex,
+    },
+    parse::{
+        attrs::WinsKind,
+        clinch::ClinchActionAst,
+        pipeline::PipelineAst,
+        signals::{SignalDef, SignalFamily},
+    },
+};
+
+use conditions::Condition;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lint {
+    /// A non-`children` step no signal derives from.
+    UnusedStep,
+    /// A signal no clinch clause or stage gate reads.
+    UnusedSignal,
+    /// A family none of whose signals are read; replaces the per-signal warnings.
+    UnusedFamily,
+    /// A clause behind an earlier clause on the same signal that always halts.
+    UnreachableRule,
+    /// A signal whose `when` contradicts itself, so it never fires.
+    UnsatisfiableSignal,
+    /// A signal with the same condition on the same step as an earlier one.
+    DuplicateSignal,
+    /// A signal that only fires when another one on the same step fires too.
+    ImpliesSignal,
+    /// A score key that one clause sets while another accumulates into it or
+    /// sets it to something else.
+    ScoreConflict,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 8] = [
+        Lint::UnusedStep,
+        Lint::UnusedSignal,
+        Lint::UnusedFamily,
+        Lint::UnreachableRule,
+        Lint::UnsatisfiableSignal,
+        Lint::DuplicateSignal,
+        Lint::ImpliesSignal,
+        Lint::ScoreConflict,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Lint::UnusedStep => "unused_step",
+            Lint::UnusedSignal => "unused_signal",
+            Lint::UnusedFamily => "unused_family",
+            Lint::UnreachableRule => "unreachable_rule",
+            Lint::UnsatisfiableSignal => "unsatisfiable_signal",
+            Lint::DuplicateSignal => "duplicate_signal",
+            Lint::ImpliesSignal => "implies_signal",
+            Lint::ScoreConflict => "score_conflict",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|lint| lint.as_str() == name)
+    }
+}
+
+pub struct Warning {
+    pub lint: Lint,
+    pub span: Span,
+    pub message: String,
+}
+
+impl Warning {
+    fn new(lint: Lint, span: Span, message: String) -> Self {
+        Self {
+            lint,
+            span,
+            message,
+        }
+    }
+}
+
+pub fn lint_pipeline(ast: &PipelineAst, index: &PipelineIndex) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+
+    lint_unused_steps(ast, index, &mut warnings);
+    lint_unused_signals(ast, index, &mut warnings);
+    lint_unreachable_rules(ast, index, &mut warnings);
+    lint_conditions(ast, index, &mut warnings);
+    lint_score_conflicts(ast, index, &mut warnings);
+
+    warnings
+}
+
+fn lint_unused_steps(ast: &PipelineAst, index: &PipelineIndex, warnings: &mut Vec<Warning>) {
+    let derived: HashSet<(&str, &str)> = index
+        .signal_index
+        .families
+        .values()
+        .flat_map(|family| family.signals.values())
+        .map(|signal| {
+            (
+                signal.derives_from.operation.as_str(),
+                signal.derives_from.step.as_str(),
+            )
+        })
+        .collect();
+
+    for (_, ops) in ast.operation_blocks() {
+        for op in &ops.definitions {
+            if op.allow.contains(&Lint::UnusedStep) {
+                continue;
+            }
+
+            let op_name = op.name.to_string();
+            for stmt in &op.body.stmts {
+                // `children` steps feed child scans and roll-ups, not signals.
+                if stmt.children.is_some()
+                    || derived.contains(&(op_name.as_str(), stmt.step.to_string().as_str()))
+                {
+                    continue;
+                }
+
+                warnings.push(Warning::new(
+                    Lint::UnusedStep,
+                    stmt.step.span(),
+                    format!(
+                        "step `{}` of operation `{}` is never derived from by a signal",
+                        stmt.step, op_name
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn lint_unused_signals(ast: &PipelineAst, index: &PipelineIndex, warnings: &mut Vec<Warning>) {
+    let used: HashSet<(String, String)> = index
+        .clinch_index
+        .clauses
+        .iter()
+        .map(|clause| &clause.signal)
+        .chain(
+            index
+                .stage_index
+                .stages
+                .iter()
+                .filter_map(|stage| stage.gate.as_ref()),
+        )
+        .map(|signal| (signal.family.clone(), signal.name.clone()))
+        .collect();
+
+    for (_, signals_block) in ast.signal_blocks() {
+        for family in &signals_block.families {
+            let family_name = family.name.to_string();
+            let unused: Vec<_> = family
+                .signals
+                .iter()
+                .filter(|signal| !used.contains(&(family_name.clone(), signal.name.to_string())))
+                .collect();
+
+            if unused.len() == family.signals.len() {
+                if !family.allow.contains(&Lint::UnusedFamily) {
+                    warnings.push(Warning::new(
+                        Lint::UnusedFamily,
+                        family.name.span(),
+                        format!(
+                            "no signal in family `{}` is used by a clinch clause or stage gate",
+                            family_name
+                        ),
+                    ));
+                }
+                continue;
+            }
+
+            for signal in unused {
+                if family.allow.contains(&Lint::UnusedSignal)
+                    || signal.allow.contains(&Lint::UnusedSignal)
+                {
+                    continue;
+                }
+
+                warnings.push(Warning::new(
+                    Lint::UnusedSignal,
+                    signal.name.span(),
+                    format!(
+                        "signal `{}.{}` is never used by a clinch clause or stage gate",
+                        family_name, signal.name
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn lint_unreachable_rules(ast: &PipelineAst, index: &PipelineIndex, warnings: &mut Vec<Warning>) {
+    let Some(clinch) = &ast.clinch else {
+        return;
+    };
+
+    // (signal, cfg, label) of every clause so far that halts
+    let mut halting: Vec<(String, Vec<String>, String)> = Vec::new();
+
+    for (clause, info) in clinch.clauses.iter().zip(&index.clinch_index.clauses) {
+        let signal = format!("{}.{}", info.signal.family, info.signal.name);
+        let cfg: Vec<String> = info
+            .cfg
+            .iter()
+            .map(|attr| attr.to_token_stream().to_string())
+            .collect();
+
+        // A halting clause that may be compiled out when this one isn't proves nothing.
+        let blocked_by = halting.iter().find(|(halt_signal, halt_cfg, _)| {
+            *halt_signal == signal && halt_cfg.iter().all(|attr| cfg.contains(attr))
+        });
+
+        if let Some((_, _, label)) = blocked_by
+            && !clause.allow.contains(&Lint::UnreachableRule)
+        {
+            let span = match &clause.rule {
+                Some(rule) => rule.id.span(),
+                None => clause.signal.name.span(),
+            };
+
+            warnings.push(Warning::new(
+                Lint::UnreachableRule,
+                span,
+                format!(
+                    "clause `{}` never runs: `{}` fires on the same signal first and halts",
+                    info.label(),
+                    label
+                ),
+            ));
+        }
+
+        if info
+            .actions
+            .iter()
+            .any(|action| matches!(action, ClinchAction::Halt))
+        {
+            halting.push((signal, cfg, info.label()));
+        }
+    }
+}
+
+fn lint_conditions(ast: &PipelineAst, index: &PipelineIndex, warnings: &mut Vec<Warning>) {
+    struct Analyzed<'a> {
+        path: String,
+        step: (String, String),
+        family: &'a SignalFamily,
+        signal: &'a SignalDef,
+        condition: Condition,
+    }
+
+    impl Analyzed<'_> {
+        fn allows(&self, lint: Lint) -> bool {
+            self.family.allow.contains(&lint) || self.signal.allow.contains(&lint)
+        }
+    }
+
+    let mut analyzed = Vec::new();
+
+    for (_, signals_block) in ast.signal_blocks() {
+        for family in &signals_block.families {
+            for signal in &family.signals {
+                let derive_from = &signal.body.derive_from;
+                let step = (
+                    derive_from.operation.to_string(),
+                    derive_from.step.to_string(),
+                );
+                let Some(binding) = index.get_step(&step.0, &step.1).map(|s| &s.binding) else {
+                    continue;
+                };
+
+                analyzed.push(Analyzed {
+                    path: format!("{}.{}", family.name, signal.name),
+                    step,
+                    family,
+                    signal,
+                    condition: Condition::parse(&signal.body.when, binding),
+                });
+            }
+        }
+    }
+
+    // An unsatisfiable condition trivially implies everything; keep it out of the pairs.
+    analyzed.retain(|a| {
+        let Some(place) = a.condition.contradiction() else {
+            return true;
+        };
+
+        if !a.allows(Lint::UnsatisfiableSignal) {
+            warnings.push(Warning::new(
+                Lint::UnsatisfiableSignal,
+                a.signal.name.span(),
+                format!(
+                    "signal `{}` can never fire: its conditions on `{}` contradict each other",
+                    a.path, place
+                ),
+            ));
+        }
+        false
+    });
+
+    for (i, a) in analyzed.iter().enumerate() {
+        for b in analyzed[i + 1..].iter().filter(|b| b.step == a.step) {
+            let a_implies_b = a.condition.implies(&b.condition);
+            let b_implies_a = b.condition.implies(&a.condition);
+
+            let (lint, signal, message) = match (a_implies_b, b_implies_a) {
+                (true, true) => (
+                    Lint::DuplicateSignal,
+                    b,
+                    format!("signal `{}` has the same condition as `{}`", b.path, a.path),
+                ),
+                (true, false) => (Lint::ImpliesSignal, a, implies_message(a, b)),
+                (false, true) => (Lint::ImpliesSignal, b, implies_message(b, a)),
+                (false, false) => continue,
+            };
+
+            if !signal.allows(lint) {
+                warnings.push(Warning::new(lint, signal.signal.name.span(), message));
+            }
+        }
+    }
+
+    fn implies_message(stronger: &Analyzed, weaker: &Analyzed) -> String {
+        format!(
+            "signal `{}` implies `{}`: whenever it fires, `{}` fires too",
+            stronger.path, weaker.path, weaker.path
+        )
+    }
+}
+
+fn lint_score_conflicts(ast: &PipelineAst, index: &PipelineIndex, warnings: &mut Vec<Warning>) {
+    struct Write<'a> {
+        clause: usize,
+        label: String,
+        /// `Some` for `=`, `None` for `+=`, `-=` and `*=`.
+        set: Option<&'a ScoreValue>,
+        span: Span,
+        allowed: bool,
+    }
+
+    let Some(clinch) = &ast.clinch else {
+        return;
+    };
+
+    // BTreeMap so warnings come out in a stable order
+    let mut writes: BTreeMap<&str, Vec<Write>> = BTreeMap::new();
+    for (i, (clause, info)) in clinch
+        .clauses
+        .iter()
+        .zip(&index.clinch_index.clauses)
+        .enumerate()
+    {
+        for (action, indexed) in clause.body.actions.iter().zip(&info.actions) {
+            if let (
+                ClinchActionAst::Score { key: ident, .. },
+                ClinchAction::Score {
+                    key,
+                    operator,
+                    value,
+                },
+            ) = (action, indexed)
+            {
+                writes.entry(key).or_default().push(Write {
+                    clause: i,
+                    label: info.label(),
+                    set: (*operator == ScoreOp::Set).then_some(value),
+                    span: ident.span(),
+                    allowed: clause.allow.contains(&Lint::ScoreConflict),
+                });
+            }
+        }
+    }
+
+    let declared: HashMap<String, WinsKind> = clinch
+        .clauses
+        .iter()
+        .flat_map(|clause| &clause.wins)
+        .map(|wins| (wins.key.to_string(), wins.kind))
+        .collect();
+
+    for (key, writes) in &writes {
+        let wins = declared.get(*key).copied();
+
+        // Clauses run in declaration order, so a declared winner holds when its
+        // writes come after every write of the other kind.
+        let conflicts = |a: &Write, b: &Write| match (a.set, b.set, wins) {
+            (Some(x), Some(y), wins) => x != y && wins != Some(WinsKind::Set),
+            (None, None, _) => false,
+            (Some(_), None, Some(WinsKind::Set)) => a.clause < b.clause,
+            (None, Some(_), Some(WinsKind::Set)) => b.clause < a.clause,
+            (Some(_), None, Some(WinsKind::Accumulate)) => a.clause > b.clause,
+            (None, Some(_), Some(WinsKind::Accumulate)) => b.clause > a.clause,
+            _ => true,
+        };
+
+        for write in writes.iter().filter(|write| !write.allowed) {
+            let others: Vec<String> = writes
+                .iter()
+                .filter(|other| other.clause != write.clause && conflicts(write, other))
+                .map(|other| {
+                    let what = match (write.set, other.set) {
+                        (Some(_), Some(_)) => "sets a different value",
+                        (_, Some(_)) => "sets",
+                        (_, None) => "accumulates",
+                    };
+                    format!("`{}` ({})", other.label, what)
+                })
+                .collect();
+
+            if others.is_empty() {
+                continue;
+            }
+
+            let hint = match wins {
+                Some(WinsKind::Set) => {
+                    "`set_wins` only holds when every set is declared after every accumulation"
+                        .to_string()
+                }
+                Some(WinsKind::Accumulate) => {
+                    "`accumulate_wins` only holds when every accumulation is declared after every set"
+                        .to_string()
+                }
+                None => format!(
+                    "declare the intended outcome with `#[lunch::set_wins({key})]` or `#[lunch::accumulate_wins({key})]`"
+                ),
+            };
+
+            warnings.push(Warning::new(
+                Lint::ScoreConflict,
+                write.span,
+                format!(
+                    "`score {}` in `{}` conflicts with {}; the result depends on which clauses fire ({})",
+                    key,
+                    write.label,
+                    others.join(", "),
+                    hint
+                ),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::warnings;
+
+    fn pipeline(signals: &str, clinch: &str) -> String {
+        format!(
+            r#"component = demo {{
+                operations {{
+                    operation magic {{
+                        do inspect_magic output magic_probe
+                    }}
+                }}
+                signals {{
+                    family format {{
+                        {signals}
+                    }}
+                }}
+                clinch {{
+                    {clinch}
+                }}
+            }}"#
+        )
+    }
+
+    const SIGNALS: &str = "
+        signal pe_magic {
+            derive from operation.magic.inspect_magic
+                when magic_probe.magic[0] == 0x4D
+        }
+        signal pdf_magic {
+            derive from operation.magic.inspect_magic
+                when magic_probe.magic[0] == 0x25
+        }";
+
+    #[test]
+    fn condition_lints_run_over_every_signal_on_a_step() {
+        let signals = "
+            signal pe_magic {
+                derive from operation.magic.inspect_magic
+                    when magic_probe.magic[0] == 0x4D
+            }
+            signal mz {
+                derive from operation.magic.inspect_magic
+                    when magic_probe.magic[0] == 0x4D
+            }
+            signal signed_pe {
+                derive from operation.magic.inspect_magic
+                    when magic_probe.signed && magic_probe.magic[0] == 0x4D
+            }
+            signal never {
+                derive from operation.magic.inspect_magic
+                    when magic_probe.magic[0] == 0x4D && magic_probe.magic[0] == 0x25
+            }";
+        let clinch = "
+            when signal.format.pe_magic { score risk += 1.0 }
+            when signal.format.mz { score risk += 1.0 }
+            when signal.format.signed_pe { score risk += 1.0 }
+            when signal.format.never { score risk += 1.0 }";
+
+        assert_eq!(
+            warnings(&pipeline(signals, clinch)),
+            [
+                "unsatisfiable_signal: signal `format.never` can never fire: its conditions on `magic_probe.magic[0]` contradict each other",
+                "duplicate_signal: signal `format.mz` has the same condition as `format.pe_magic`",
+                "implies_signal: signal `format.signed_pe` implies `format.pe_magic`: whenever it fires, `format.pe_magic` fires too",
+                "implies_signal: signal `format.signed_pe` implies `format.mz`: whenever it fires, `format.mz` fires too",
+            ]
+        );
+    }
+
+    #[test]
+    fn allow_silences_a_condition_lint() {
+        let signals = "
+            signal pe_magic {
+                derive from operation.magic.inspect_magic
+                    when magic_probe.magic[0] == 0x4D
+            }
+            #[allow(lunch::duplicate_signal)]
+            signal mz {
+                derive from operation.magic.inspect_magic
+                    when magic_probe.magic[0] == 0x4D
+            }";
+        let clinch = "
+            when signal.format.pe_magic { score risk += 1.0 }
+            when signal.format.mz { score risk += 1.0 }";
+
+        assert_eq!(warnings(&pipeline(signals, clinch)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn set_and_accumulate_on_one_key_conflict() {
+        let clinch = "
+            when signal.format.pe_magic { score risk = 5.0 }
+            when signal.format.pdf_magic { score risk += 1.0 }";
+
+        let warnings = warnings(&pipeline(SIGNALS, clinch));
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().all(|w| w.starts_with("score_conflict: ")));
+    }
+
+    #[test]
+    fn sets_to_different_values_conflict() {
+        let clinch = "
+            when signal.format.pe_magic { score risk = 5.0 }
+            when signal.format.pdf_magic { score risk = 1.0 }";
+
+        assert_eq!(warnings(&pipeline(SIGNALS, clinch)).len(), 2);
+    }
+
+    #[test]
+    fn sets_to_the_same_value_and_accumulations_alone_do_not_conflict() {
+        let clinch = "
+            when signal.format.pe_magic { score risk = 5.0 score trust += 1.0 }
+            when signal.format.pdf_magic { score risk = 5.0 score trust -= 1.0 }";
+
+        assert_eq!(warnings(&pipeline(SIGNALS, clinch)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn set_wins_holds_when_sets_come_last() {
+        let clinch = "
+            when signal.format.pdf_magic { score risk += 1.0 }
+            #[lunch::set_wins(risk)]
+            when signal.format.pe_magic { score risk = 5.0 }";
+
+        assert_eq!(warnings(&pipeline(SIGNALS, clinch)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn set_wins_that_does_not_hold_keeps_the_warnings() {
+        let clinch = "
+            #[lunch::set_wins(risk)]
+            when signal.format.pe_magic { score risk = 5.0 }
+            when signal.format.pdf_magic { score risk += 1.0 }";
+
+        assert_eq!(warnings(&pipeline(SIGNALS, clinch)).len(), 2);
+    }
+
+    #[test]
+    fn accumulate_wins_holds_when_accumulations_come_last() {
+        let clinch = "
+            when signal.format.pe_magic { score risk = 5.0 }
+            #[lunch::accumulate_wins(risk)]
+            when signal.format.pdf_magic { score risk += 1.0 }";
+
+        assert_eq!(warnings(&pipeline(SIGNALS, clinch)), Vec::<String>::new());
+    }
+}
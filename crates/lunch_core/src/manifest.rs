@@ -0,0 +1,152 @@
+/*
+
+Provenance manifest built from the indexed pipeline: operations, signals, rules,
+params and profiles as JSON. Keys are sorted so the output is deterministic.
+
+*/
+
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+use crate::index::{
+    clinch::{ClinchAction, ScoreValue},
+    params::ParamValue,
+    pipeline::PipelineIndex,
+    rollup::RollupInfo,
+};
+
+pub fn build_manifest(index: &PipelineIndex, component: &str) -> Value {
+    // BTreeMaps keep the output deterministic regardless of index hashing.
+    let operations: BTreeMap<&String, Vec<&String>> = index
+        .operation_index
+        .index
+        .iter()
+        .map(|(name, op)| {
+            let mut steps: Vec<&String> = op.steps.keys().collect();
+            steps.sort();
+            (name, steps)
+        })
+        .collect();
+
+    let mut signals: Vec<String> = index
+        .signal_index
+        .families
+        .iter()
+        .flat_map(|(family, info)| {
+            info.signals
+                .keys()
+                .map(move |name| format!("{}.{}", family, name))
+        })
+        .collect();
+    signals.sort();
+
+    let rules: Vec<Value> = index
+        .clinch_index
+        .clauses
+        .iter()
+        .filter_map(|clause| {
+            clause.rule.as_ref().map(|rule| {
+                json!({
+                    "id": rule.id,
+                    "title": rule.title,
+                    "severity": rule.severity.as_str(),
+                    "signal": format!("{}.{}", clause.signal.family, clause.signal.name),
+                })
+            })
+        })
+        .collect();
+
+    let mut pipelines: Vec<&String> = index
+        .clinch_index
+        .clauses
+        .iter()
+        .flat_map(|clause| &clause.actions)
+        .filter_map(|action| match action {
+            ClinchAction::RunPipeline { component, .. } => Some(component),
+            _ => None,
+        })
+        .collect();
+    pipelines.sort();
+    pipelines.dedup();
+
+    let rollup: BTreeMap<String, String> = index
+        .rollup_index
+        .rules
+        .iter()
+        .map(|rule| match rule {
+            RollupInfo::Score { key, func } => {
+                (key.clone(), format!("{}(children)", func.as_str()))
+            }
+            RollupInfo::Tags { prefix } => {
+                ("tags".to_string(), format!("union prefixed {:?}", prefix))
+            }
+            RollupInfo::Verdict => ("verdict".to_string(), "max(children)".to_string()),
+        })
+        .collect();
+
+    let stages: Vec<Value> = index
+        .stage_index
+        .stages
+        .iter()
+        .map(|stage| {
+            json!({
+                "name": stage.name,
+                "gate": stage.gate.as_ref().map(|gate| format!("{}.{}", gate.family, gate.name)),
+            })
+        })
+        .collect();
+
+    let params: BTreeMap<&String, Value> = index
+        .param_index
+        .params
+        .iter()
+        .map(|param| {
+            let default = match param.value {
+                ParamValue::Float(v) => json!(v),
+                ParamValue::Int(v) if v < 0 => json!(v as i64),
+                ParamValue::Int(v) => json!(v as u64),
+                ParamValue::Bool(v) => json!(v),
+            };
+            (
+                &param.name,
+                json!({ "type": param.ty.as_str(), "default": default }),
+            )
+        })
+        .collect();
+
+    let profiles: Vec<Value> = index
+        .profile_index
+        .profiles
+        .iter()
+        .map(|profile| {
+            let overrides: Vec<Value> = profile
+                .overrides
+                .iter()
+                .map(|o| {
+                    let value = match &o.value {
+                        ScoreValue::Number(n) => json!(n),
+                        ScoreValue::Param(param) => json!(param),
+                    };
+                    json!({
+                        "score": o.key,
+                        "signal": format!("{}.{}", o.signal.family, o.signal.name),
+                        "value": value,
+                    })
+                })
+                .collect();
+            json!({ "name": profile.name, "overrides": overrides })
+        })
+        .collect();
+
+    json!({
+        "component": component,
+        "params": params,
+        "operations": operations,
+        "signals": signals,
+        "stages": stages,
+        "rules": rules,
+        "pipelines": pipelines,
+        "rollup": rollup,
+        "profiles": profiles,
+    })
+}
@@ -0,0 +1,124 @@
+use syn::{
+    Attribute, Ident, Path, Token,
+    parse::{ParseStream, Result},
+    punctuated::Punctuated,
+};
+
+use crate::{lints::Lint, validate::core_validate::suggest};
+
+/// Outer attributes on a DSL item.
+pub struct ItemAttrs {
+    /// `#[cfg(...)]`, carried through to the lowered statements.
+    pub cfg: Vec<Attribute>,
+    /// `#[allow(lunch::<lint>)]`, read by the lint pass and then dropped.
+    pub allow: Vec<Lint>,
+    /// `#[lunch::set_wins(<key>, ..)]` and `#[lunch::accumulate_wins(<key>, ..)]`;
+    /// clinch clauses only.
+    pub wins: Vec<ScoreWins>,
+}
+
+/// Declared outcome for a score key that is both set and accumulated.
+#[derive(Debug, Clone)]
+pub struct ScoreWins {
+    pub kind: WinsKind,
+    pub key: Ident,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WinsKind {
+    Set,
+    Accumulate,
+}
+
+impl WinsKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WinsKind::Set => "set_wins",
+            WinsKind::Accumulate => "accumulate_wins",
+        }
+    }
+}
+
+/// `wins_allowed` is set for clinch clauses, the only items that write scores.
+pub fn parse_item_attrs(input: ParseStream, wins_allowed: bool) -> Result<ItemAttrs> {
+    let mut cfg = Vec::new();
+    let mut allow = Vec::new();
+    let mut wins = Vec::new();
+
+    for attr in input.call(Attribute::parse_outer)? {
+        if let Some(kind) = wins_kind(attr.path()) {
+            if !wins_allowed {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    format!(
+                        "`#[lunch::{}]` only applies to clinch clauses",
+                        kind.as_str()
+                    ),
+                ));
+            }
+
+            let keys = attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?;
+            wins.extend(keys.into_iter().map(|key| ScoreWins { kind, key }));
+        } else if attr.path().is_ident("cfg") {
+            cfg.push(attr);
+        } else if attr.path().is_ident("allow") {
+            let paths = attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)?;
+            for path in &paths {
+                allow.push(parse_lint(path)?);
+            }
+        } else {
+            let message = if wins_allowed {
+                "only `#[cfg(...)]`, `#[allow(lunch::<lint>)]`, `#[lunch::set_wins(..)]` and `#[lunch::accumulate_wins(..)]` attributes are supported here"
+            } else {
+                "only `#[cfg(...)]` and `#[allow(lunch::<lint>)]` attributes are supported here"
+            };
+            return Err(syn::Error::new_spanned(attr, message));
+        }
+    }
+
+    Ok(ItemAttrs { cfg, allow, wins })
+}
+
+fn wins_kind(path: &Path) -> Option<WinsKind> {
+    let mut segments = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string());
+    match (
+        segments.next()?.as_str(),
+        segments.next()?.as_str(),
+        segments.next(),
+    ) {
+        ("lunch", "set_wins", None) => Some(WinsKind::Set),
+        ("lunch", "accumulate_wins", None) => Some(WinsKind::Accumulate),
+        _ => None,
+    }
+}
+
+/// `lunch::<lint>`; rustc's own lints mean nothing inside the DSL.
+fn parse_lint(path: &Path) -> Result<Lint> {
+    let segments: Vec<String> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+
+    let [tool, name] = segments.as_slice() else {
+        return Err(syn::Error::new_spanned(path, "expected `lunch::<lint>`"));
+    };
+
+    if tool != "lunch" {
+        return Err(syn::Error::new_spanned(path, "expected `lunch::<lint>`"));
+    }
+
+    Lint::from_name(name).ok_or_else(|| {
+        syn::Error::new_spanned(
+            path,
+            format!(
+                "unknown lint `lunch::{}`{}",
+                name,
+                suggest(name, Lint::ALL.iter().map(|lint| lint.as_str()))
+            ),
+        )
+    })
+}
@@ -0,0 +1,478 @@
+use crate::{
+    diagnostics::{Diagnostics, parse_item, parse_until},
+    lints::Lint,
+    parse::{
+        attrs::{ItemAttrs, ScoreWins, parse_item_attrs},
+        kw,
+    },
+};
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use quote::ToTokens;
+use std::fmt;
+use syn::{
+    Attribute, Ident, Lit, LitStr, Member, Path, Token, braced,
+    ext::IdentExt,
+    parse::{Parse, ParseStream, Result},
+    punctuated::Punctuated,
+    token,
+};
+
+#[derive(Debug)]
+pub struct ClinchBlock {
+    pub clauses: Vec<ClinchClause>,
+}
+
+#[derive(Debug)]
+pub struct ClinchClause {
+    pub attrs: Vec<Attribute>,
+    pub allow: Vec<Lint>,
+    pub wins: Vec<ScoreWins>,
+    pub rule: Option<RuleMeta>,
+    pub signal: SignalPath,
+    pub body: ClinchBody,
+}
+
+/// `rule PDF-001 "PDF magic header" severity low`
+#[derive(Debug)]
+pub struct RuleMeta {
+    pub id: RuleId,
+    pub title: LitStr,
+    pub severity: Ident,
+}
+
+/// Rule IDs are free-form token runs such as `PDF-001`, so they are kept as
+/// tokens (for spans) alongside their joined text.
+#[derive(Debug, Clone)]
+pub struct RuleId {
+    pub text: String,
+    pub tokens: TokenStream2,
+}
+
+impl fmt::Display for RuleId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl ToTokens for RuleId {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        self.tokens.to_tokens(tokens);
+    }
+}
+
+#[derive(Debug)]
+pub struct SignalPath {
+    pub family: Ident,
+    pub name: Ident,
+}
+
+#[derive(Debug)]
+pub struct ClinchBody {
+    pub brace: token::Brace,
+    pub actions: Vec<ClinchActionAst>,
+}
+
+#[derive(Debug)]
+pub enum ClinchActionAst {
+    /// `emit Emit::HighEntropy [{ entropy = entropy_probe.entropy, ... }]`
+    Emit {
+        event: syn::Path,
+        payload: Option<EmitPayload>,
+    },
+    /// `tag += <value>` (no key) or `tag <key> = <value>`
+    Tag {
+        key: Option<Ident>,
+        value: TagValueAst,
+    },
+    /// `score <key> <op> <value>`
+    Score {
+        key: Ident,
+        op: ScoreOpAst,
+        value: ScoreValueAst,
+    },
+    /// `run deferred <Handler>`
+    RunDeferred {
+        handler: Ident,
+    },
+    /// `run pipeline [<module>::]<component>`
+    RunPipeline {
+        /// Where the component's mint module lives, when it isn't imported.
+        module: Option<Path>,
+        component: Ident,
+    },
+    /// `verdict <level>`
+    Verdict {
+        level: Ident,
+    },
+    Halt,
+}
+
+#[derive(Debug)]
+pub struct EmitPayload {
+    pub brace: token::Brace,
+    pub fields: Punctuated<EmitField, Token![,]>,
+}
+
+/// `<field> = <expr over the clause's binding>`
+#[derive(Debug)]
+pub struct EmitField {
+    pub name: Ident,
+    pub value: syn::Expr,
+}
+
+#[derive(Debug)]
+pub enum TagValueAst {
+    /// `"type:pdf"`, possibly with `{binding.field}` interpolations.
+    Literal(LitStr),
+    /// `format_probe.format`; only after `tag <key> =`.
+    Binding(BindingPath),
+}
+
+/// `<binding>.<field>...`, e.g. `format_probe.format` or `probe.magic.0`.
+#[derive(Debug)]
+pub struct BindingPath {
+    pub root: Ident,
+    pub fields: Vec<Member>,
+}
+
+#[derive(Debug)]
+pub enum ScoreOpAst {
+    Add,
+    Sub,
+    Mul,
+    Set,
+}
+
+/// Score values in clinch and in `profile` overrides.
+#[derive(Debug)]
+pub enum ScoreValueAst {
+    /// Leading `-` kept apart, as in `params`.
+    Literal {
+        negative: Option<Token![-]>,
+        lit: Lit,
+    },
+    Param(Ident),
+}
+
+impl Parse for ClinchBlock {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        braced!(content in input);
+
+        let mut diags = Diagnostics::new();
+        let mut clauses = Vec::new();
+        while !content.is_empty() {
+            if let Some(clause) = parse_item(&content, &mut diags) {
+                clauses.push(clause);
+            }
+        }
+        diags.finish()?;
+
+        Ok(Self { clauses })
+    }
+}
+
+impl Parse for ClinchClause {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ItemAttrs {
+            cfg: attrs,
+            allow,
+            wins,
+        } = parse_item_attrs(input, true)?;
+
+        // Optional `rule <ID> "<title>" severity <level>`
+        let rule = if input.peek(kw::rule) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        // Parse `when`
+        if !input.peek(kw::when) {
+            return Err(input.error(
+                "expected `when signal.<family>.<name> { ... }` or `rule <ID> \"<title>\" severity <level> when ...`",
+            ));
+        }
+        input.parse::<kw::when>()?;
+
+        // Parse `signal.<family>.<name>`
+        let signal: SignalPath = input.parse()?;
+
+        // Parse `{ ... }`
+        let body: ClinchBody = input.parse()?;
+
+        Ok(Self {
+            attrs,
+            allow,
+            wins,
+            rule,
+            signal,
+            body,
+        })
+    }
+}
+
+impl Parse for RuleMeta {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let kw_rule: kw::rule = input.parse()?;
+
+        // ID runs until the title literal, e.g. `PDF` `-` `001`
+        let mut id_tokens = Vec::new();
+        while !input.is_empty() && !input.peek(LitStr) {
+            match input.parse::<TokenTree>()? {
+                TokenTree::Group(g) => {
+                    return Err(syn::Error::new_spanned(g, "unexpected group in rule ID"));
+                }
+                tt => id_tokens.push(tt),
+            }
+        }
+
+        if id_tokens.is_empty() {
+            return Err(syn::Error::new_spanned(
+                kw_rule,
+                "expected rule ID after `rule`",
+            ));
+        }
+
+        let text = id_tokens.iter().map(|t| t.to_string()).collect();
+        let id = RuleId {
+            text,
+            tokens: id_tokens.into_iter().collect(),
+        };
+
+        let title: LitStr = input.parse()?;
+
+        if !input.peek(kw::severity) {
+            return Err(input.error("expected `severity <level>` after rule title"));
+        }
+        input.parse::<kw::severity>()?;
+
+        let severity: Ident = input.parse()?;
+
+        Ok(Self {
+            id,
+            title,
+            severity,
+        })
+    }
+}
+
+impl Parse for SignalPath {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if !input.peek(kw::signal) {
+            return Err(input.error("expected `signal.<family>.<name>`"));
+        }
+        input.parse::<kw::signal>()?;
+
+        input.parse::<Token![.]>()?;
+        let family: Ident = input.parse()?;
+
+        input.parse::<Token![.]>()?;
+        let name: Ident = input.parse()?;
+
+        Ok(Self { family, name })
+    }
+}
+
+impl Parse for ClinchBody {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        let brace = braced!(content in input);
+
+        let mut diags = Diagnostics::new();
+        let mut actions = Vec::new();
+        while !content.is_empty() {
+            if let Some(action) = parse_until(&content, &mut diags, at_action_start) {
+                actions.push(action);
+            }
+        }
+        diags.finish()?;
+
+        Ok(Self { brace, actions })
+    }
+}
+
+impl Parse for ClinchActionAst {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let lookahead = input.lookahead1();
+
+        if lookahead.peek(kw::emit) {
+            input.parse::<kw::emit>()?;
+            let event = syn::Path::parse_mod_style(input)?;
+            let payload = if input.peek(token::Brace) {
+                Some(input.parse()?)
+            } else {
+                None
+            };
+
+            Ok(ClinchActionAst::Emit { event, payload })
+        } else if lookahead.peek(kw::tag) {
+            input.parse::<kw::tag>()?;
+
+            // tag keys are often keywords (`type`)
+            let key = if input.peek(Token![+=]) {
+                input.parse::<Token![+=]>()?;
+                None
+            } else {
+                let key = input.call(Ident::parse_any)?;
+                input.parse::<Token![=]>()?;
+                Some(key)
+            };
+
+            let value = if input.peek(LitStr) {
+                TagValueAst::Literal(input.parse()?)
+            } else if key.is_some() && input.peek(Ident) {
+                TagValueAst::Binding(input.parse()?)
+            } else if key.is_some() {
+                return Err(input
+                    .error("expected string literal or `<binding>.<field>` after `tag <key> =`"));
+            } else {
+                return Err(input.error("expected string literal after `tag +=`"));
+            };
+
+            Ok(ClinchActionAst::Tag { key, value })
+        } else if lookahead.peek(kw::score) {
+            input.parse::<kw::score>()?;
+            let key = input.call(Ident::parse_any)?;
+            let op = input.parse()?;
+            let value = input.parse()?;
+
+            Ok(ClinchActionAst::Score { key, op, value })
+        } else if lookahead.peek(kw::run) {
+            input.parse::<kw::run>()?;
+
+            let lookahead = input.lookahead1();
+            if lookahead.peek(kw::deferred) {
+                input.parse::<kw::deferred>()?;
+                Ok(ClinchActionAst::RunDeferred {
+                    handler: input.parse()?,
+                })
+            } else if lookahead.peek(kw::pipeline) {
+                input.parse::<kw::pipeline>()?;
+                let mut module = input.call(Path::parse_mod_style)?;
+                let component = module
+                    .segments
+                    .pop()
+                    .map(|segment| segment.into_value().ident)
+                    .expect("a parsed path has at least one segment");
+                Ok(ClinchActionAst::RunPipeline {
+                    module: (!module.segments.is_empty()).then(|| {
+                        module.segments.pop_punct();
+                        module
+                    }),
+                    component,
+                })
+            } else {
+                Err(lookahead.error())
+            }
+        } else if lookahead.peek(kw::verdict) {
+            input.parse::<kw::verdict>()?;
+            Ok(ClinchActionAst::Verdict {
+                level: input.parse()?,
+            })
+        } else if lookahead.peek(kw::halt) {
+            input.parse::<kw::halt>()?;
+            Ok(ClinchActionAst::Halt)
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+fn at_action_start(input: ParseStream) -> bool {
+    input.peek(kw::emit)
+        || input.peek(kw::tag)
+        || input.peek(kw::score)
+        || input.peek(kw::run)
+        || input.peek(kw::verdict)
+        || input.peek(kw::halt)
+}
+
+impl Parse for EmitPayload {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        let brace = braced!(content in input);
+        let fields = content.parse_terminated(EmitField::parse, Token![,])?;
+
+        Ok(Self { brace, fields })
+    }
+}
+
+impl Parse for EmitField {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let value = input.parse()?;
+
+        Ok(Self { name, value })
+    }
+}
+
+impl Parse for BindingPath {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let root: Ident = input.parse()?;
+
+        let mut fields = Vec::new();
+        while input.peek(Token![.]) && (input.peek2(Ident) || input.peek2(syn::LitInt)) {
+            input.parse::<Token![.]>()?;
+            fields.push(input.parse()?);
+        }
+
+        Ok(Self { root, fields })
+    }
+}
+
+impl ToTokens for BindingPath {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        self.root.to_tokens(tokens);
+        for field in &self.fields {
+            <Token![.]>::default().to_tokens(tokens);
+            field.to_tokens(tokens);
+        }
+    }
+}
+
+impl Parse for ScoreOpAst {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let lookahead = input.lookahead1();
+
+        if lookahead.peek(Token![+=]) {
+            input.parse::<Token![+=]>()?;
+            Ok(ScoreOpAst::Add)
+        } else if lookahead.peek(Token![-=]) {
+            input.parse::<Token![-=]>()?;
+            Ok(ScoreOpAst::Sub)
+        } else if lookahead.peek(Token![*=]) {
+            input.parse::<Token![*=]>()?;
+            Ok(ScoreOpAst::Mul)
+        } else if lookahead.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Ok(ScoreOpAst::Set)
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl Parse for ScoreValueAst {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(Ident) {
+            return input.parse().map(ScoreValueAst::Param);
+        }
+
+        let negative = if input.peek(Token![-]) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        if !input.peek(Lit) {
+            return Err(input.error("expected number or param as score value"));
+        }
+
+        Ok(ScoreValueAst::Literal {
+            negative,
+            lit: input.parse()?,
+        })
+    }
+}
@@ -0,0 +1,30 @@
+/*
+
+DSL keywords. `do`, `for` and `in` are Rust keywords and use `Token![..]`
+instead. Keywords peek without consuming, so parsers can branch on them.
+
+*/
+
+syn::custom_keyword!(operation);
+syn::custom_keyword!(output);
+syn::custom_keyword!(children);
+
+syn::custom_keyword!(family);
+syn::custom_keyword!(signal);
+syn::custom_keyword!(derive);
+syn::custom_keyword!(from);
+syn::custom_keyword!(when);
+
+syn::custom_keyword!(rule);
+syn::custom_keyword!(severity);
+
+syn::custom_keyword!(emit);
+syn::custom_keyword!(tag);
+syn::custom_keyword!(score);
+syn::custom_keyword!(run);
+syn::custom_keyword!(deferred);
+syn::custom_keyword!(pipeline);
+syn::custom_keyword!(verdict);
+syn::custom_keyword!(halt);
+
+syn::custom_keyword!(file);
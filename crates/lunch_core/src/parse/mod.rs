@@ -0,0 +1,11 @@
+pub mod attrs;
+pub mod clinch;
+pub mod kw;
+pub mod operations;
+pub mod params;
+pub mod pipeline;
+pub mod profile;
+pub mod rollup;
+pub mod signals;
+pub mod stage;
+pub mod suppress;
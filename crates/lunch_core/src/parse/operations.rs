@@ -0,0 +1,121 @@
+// -------------------------------------------------------------------------
+// Operations
+// -------------------------------------------------------------------------
+
+use crate::{
+    diagnostics::{Diagnostics, parse_item, parse_until},
+    lints::Lint,
+    parse::{
+        attrs::{ItemAttrs, parse_item_attrs},
+        kw,
+    },
+};
+use syn::{
+    Attribute, Ident, Token, braced,
+    parse::{Parse, ParseStream, Result},
+};
+
+pub struct OperationBody {
+    pub stmts: Vec<DoStmt>,
+}
+
+/// `do <step> output <binding> [children]`
+pub struct DoStmt {
+    pub step: Ident,
+    pub output: Ident,
+    pub children: Option<kw::children>,
+}
+
+impl Parse for OperationBody {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        braced!(content in input);
+
+        let mut diags = Diagnostics::new();
+        let mut stmts = Vec::new();
+        while !content.is_empty() {
+            if let Some(stmt) = parse_until(&content, &mut diags, |input| input.peek(Token![do])) {
+                stmts.push(stmt);
+            }
+        }
+        diags.finish()?;
+
+        Ok(Self { stmts })
+    }
+}
+
+impl Parse for DoStmt {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if !input.peek(Token![do]) {
+            return Err(input.error("expected `do <step> output <name> [children]`"));
+        }
+
+        input.parse::<Token![do]>()?;
+        let step: Ident = input.parse()?;
+
+        if !input.peek(kw::output) {
+            return Err(input.error("expected `output <name>` after the step"));
+        }
+
+        input.parse::<kw::output>()?;
+        let output: Ident = input.parse()?;
+        let children = input.parse()?;
+
+        Ok(Self {
+            step,
+            output,
+            children,
+        })
+    }
+}
+
+pub struct OperationDef {
+    pub attrs: Vec<Attribute>,
+    pub allow: Vec<Lint>,
+    pub name: Ident,
+    pub body: OperationBody,
+}
+
+impl Parse for OperationDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ItemAttrs {
+            cfg: attrs, allow, ..
+        } = parse_item_attrs(input, false)?;
+        if !input.peek(kw::operation) {
+            return Err(input.error("expected `operation <name> { ... }`"));
+        }
+
+        input.parse::<kw::operation>()?;
+        let name: Ident = input.parse()?;
+        let body: OperationBody = input.parse()?;
+
+        Ok(Self {
+            attrs,
+            allow,
+            name,
+            body,
+        })
+    }
+}
+
+pub struct OperationsBlock {
+    pub definitions: Vec<OperationDef>,
+}
+
+impl Parse for OperationsBlock {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        braced!(content in input);
+
+        let mut diags = Diagnostics::new();
+        let mut definitions = Vec::new();
+        while !content.is_empty() {
+            if let Some(def) = parse_item(&content, &mut diags) {
+                definitions.push(def);
+            }
+        }
+        diags.finish()?;
+
+        Ok(Self { definitions })
+    }
+}
@@ -0,0 +1,64 @@
+use crate::diagnostics::{Diagnostics, parse_until};
+use syn::{
+    Ident, Lit, Token, braced,
+    parse::{Parse, ParseStream, Result},
+};
+
+/// `params { HIGH_ENTROPY: f64 = 7.0, PE_RISK: f64 = 3.0 }`
+pub struct ParamsBlock {
+    pub params: Vec<ParamDef>,
+}
+
+pub struct ParamDef {
+    pub name: Ident,
+    pub ty: Ident,
+    /// Leading `-`, kept apart because `-1.0` is two tokens.
+    pub negative: Option<Token![-]>,
+    pub value: Lit,
+}
+
+impl Parse for ParamsBlock {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        braced!(content in input);
+
+        let mut diags = Diagnostics::new();
+        let mut params = Vec::new();
+        while !content.is_empty() {
+            let at_next = |input: ParseStream| input.peek(Ident) && input.peek2(Token![:]);
+            if let Some(param) = parse_until(&content, &mut diags, at_next) {
+                params.push(param);
+            }
+
+            if content.peek(Token![,]) {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        diags.finish()?;
+
+        Ok(Self { params })
+    }
+}
+
+impl Parse for ParamDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name: Ident = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+
+        let negative = if input.peek(Token![-]) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        let value: Lit = input.parse()?;
+
+        Ok(Self {
+            name,
+            ty,
+            negative,
+            value,
+        })
+    }
+}
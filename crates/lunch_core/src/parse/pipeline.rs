@@ -0,0 +1,285 @@
+//use common::luop_descriptor::LuopDescriptor;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{Ident, Token, braced};
+
+use crate::diagnostics::{Diagnostics, parse_item, parse_until, skip_item};
+use crate::index::pipeline::PipelineIndex;
+use crate::parse::clinch::ClinchBlock;
+use crate::parse::operations::OperationsBlock;
+use crate::parse::params::ParamsBlock;
+use crate::parse::profile::ProfileBlock;
+use crate::parse::rollup::RollupBlock;
+use crate::parse::signals::SignalsBlock;
+use crate::parse::stage::StageBlock;
+use crate::parse::suppress::SuppressBlock;
+use crate::validate::cfg::validate_cfg;
+use crate::validate::clinch::validate_clinch;
+use crate::validate::operations::validate_operations;
+use crate::validate::params::validate_params;
+use crate::validate::profiles::validate_profiles;
+use crate::validate::rollup::validate_rollup;
+use crate::validate::signals::validate_signals;
+use crate::validate::suppress::validate_suppress;
+
+// -------------------------------------------------------------------------
+// Top-level blocks
+// Important file
+// -------------------------------------------------------------------------
+
+pub struct MetaBlock {
+    pub name: Ident,
+    pub content: TokenStream2,
+}
+
+pub struct PipelineAst {
+    pub meta: Option<MetaBlock>,
+    pub params: Option<ParamsBlock>,
+    pub operations: Option<OperationsBlock>,
+    pub rollup: Option<RollupBlock>,
+    pub signals: Option<SignalsBlock>,
+    pub stages: Vec<StageBlock>,
+    pub suppress: Option<SuppressBlock>,
+    pub clinch: Option<ClinchBlock>,
+    pub profiles: Vec<ProfileBlock>,
+}
+
+impl PipelineAst {
+    /// Every `operations` block with the stage it belongs to; top-level first.
+    pub fn operation_blocks(
+        &self,
+    ) -> impl Iterator<Item = (Option<&StageBlock>, &OperationsBlock)> {
+        self.operations.iter().map(|ops| (None, ops)).chain(
+            self.stages
+                .iter()
+                .filter_map(|stage| stage.operations.as_ref().map(|ops| (Some(stage), ops))),
+        )
+    }
+
+    /// Every `signals` block with the stage it belongs to; top-level first.
+    pub fn signal_blocks(&self) -> impl Iterator<Item = (Option<&StageBlock>, &SignalsBlock)> {
+        self.signals.iter().map(|signals| (None, signals)).chain(
+            self.stages
+                .iter()
+                .filter_map(|stage| stage.signals.as_ref().map(|signals| (Some(stage), signals))),
+        )
+    }
+
+    /*
+
+    Very important method
+
+     */
+
+    pub fn validate_and_index(&self, component: &Ident) -> Result<PipelineIndex> {
+        // 1. Parse already happened
+
+        // Errors are collected across every step below and reported together.
+        // Only a missing required block stops early.
+        let mut diags = Diagnostics::new();
+
+        // 2. Validate operations (purely local)
+        validate_operations(self, &mut diags)?;
+
+        // 3. Build operation index
+        let mut index = PipelineIndex::from_operations(self)?;
+
+        // 3a. Validate + index params (referenced from `when` and `score`)
+        validate_params(self, &mut diags);
+        index.extend_with_params(self);
+
+        // 3b. Validate + index roll-ups (need `children` steps)
+        validate_rollup(self, &index.operation_index, &mut diags);
+        index.extend_with_rollup(self);
+
+        // 4. Validate signals *against operation index*
+        validate_signals(self, &index.operation_index, &index.param_index, &mut diags)?;
+
+        //validate_operations_against_luops(&mut index.operation_index, luops)?;
+
+        // 5. Build signal index
+        index.extend_with_signals(self)?;
+
+        // 5b. Index stages; checks gates and derivations against stage order
+        index.extend_with_stages(self, &mut diags);
+
+        // 6. Validate + index suppressions *against signal index*
+        validate_suppress(
+            self,
+            &index.signal_index,
+            &index.operation_index,
+            &index.param_index,
+            &mut diags,
+        );
+        index.extend_with_suppress(self);
+
+        // 7. Validate clinch *against signal index*
+        validate_clinch(
+            self,
+            component,
+            &index.signal_index,
+            &index.operation_index,
+            &index.param_index,
+            &mut diags,
+        )?;
+
+        // 8. Build clinch index
+        index.extend_with_clinch(self)?;
+
+        // 8b. Validate + index profiles *against clinch index*
+        validate_profiles(self, &index.clinch_index, &index.param_index, &mut diags);
+        index.extend_with_profiles(self);
+
+        // 9. `#[cfg]` consistency across everything indexed
+        validate_cfg(self, &index, &mut diags);
+
+        diags.finish()?;
+
+        Ok(index)
+    }
+}
+// -------------------------------------------------------------------------
+// Pipeline parser: meta/params/operations/rollup/signals/stage/suppress/clinch/profile
+// -------------------------------------------------------------------------
+
+impl Parse for PipelineAst {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut meta: Option<MetaBlock> = None;
+        let mut params: Option<ParamsBlock> = None;
+        let mut operations: Option<OperationsBlock> = None;
+        let mut rollup: Option<RollupBlock> = None;
+        let mut signals: Option<SignalsBlock> = None;
+        let mut stages: Vec<StageBlock> = Vec::new();
+        let mut suppress: Option<SuppressBlock> = None;
+        let mut clinch: Option<ClinchBlock> = None;
+        let mut profiles: Vec<ProfileBlock> = Vec::new();
+
+        // A broken block is recorded and skipped so the blocks after it still parse.
+        let mut diags = Diagnostics::new();
+
+        while !input.is_empty() {
+            let Some(name) = parse_until::<Ident>(input, &mut diags, |input| input.peek(Ident))
+            else {
+                continue;
+            };
+
+            match name.to_string().as_str() {
+                "meta" => {
+                    let content = parse_item::<MetaContent>(input, &mut diags);
+                    let block = content.map(|content| MetaBlock {
+                        name: name.clone(),
+                        content: content.0,
+                    });
+                    parse_once(&mut meta, block, &name, &mut diags);
+                }
+                "params" => {
+                    let block = parse_item(input, &mut diags);
+                    parse_once(&mut params, block, &name, &mut diags);
+                }
+                "operations" => {
+                    let block = parse_item(input, &mut diags);
+                    parse_once(&mut operations, block, &name, &mut diags);
+                }
+                "rollup" => {
+                    let block = parse_item(input, &mut diags);
+                    parse_once(&mut rollup, block, &name, &mut diags);
+                }
+                "signals" => {
+                    let block = parse_item(input, &mut diags);
+                    parse_once(&mut signals, block, &name, &mut diags);
+                }
+                "stage" => {
+                    stages.extend(parse_item(input, &mut diags));
+                }
+                "suppress" => {
+                    let block = parse_item(input, &mut diags);
+                    parse_once(&mut suppress, block, &name, &mut diags);
+                }
+                "clinch" => {
+                    let block = parse_item(input, &mut diags);
+                    parse_once(&mut clinch, block, &name, &mut diags);
+                }
+                "profile" => {
+                    profiles.extend(parse_item(input, &mut diags));
+                }
+                _ => {
+                    diags.push(syn::Error::new_spanned(
+                        name,
+                        "expected one of: meta, params, operations, rollup, signals, stage, suppress, clinch, profile",
+                    ));
+                    skip_item(input);
+                }
+            }
+        }
+
+        diags.finish()?;
+
+        Ok(Self {
+            meta,
+            params,
+            operations,
+            rollup,
+            signals,
+            stages,
+            suppress,
+            clinch,
+            profiles,
+        })
+    }
+}
+
+/// Store a block that may appear once; a second one is reported and dropped.
+fn parse_once<T>(slot: &mut Option<T>, block: Option<T>, name: &Ident, diags: &mut Diagnostics) {
+    if slot.is_some() {
+        diags.push(syn::Error::new_spanned(
+            name,
+            format!("duplicate `{}` block", name),
+        ));
+    } else {
+        *slot = block;
+    }
+}
+
+/// `meta { ... }` is kept as raw tokens for now.
+struct MetaContent(TokenStream2);
+
+impl Parse for MetaContent {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        braced!(content in input);
+        Ok(Self(content.parse()?))
+    }
+}
+
+// -------------------------------------------------------------------------
+// clinchgen!(component = <ident> { ... })
+// -------------------------------------------------------------------------
+
+pub struct LunchGenInput {
+    pub component: Ident,
+    pub pipeline: PipelineAst,
+}
+
+impl Parse for LunchGenInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let key: Ident = input.parse()?;
+        if key != "component" {
+            return Err(syn::Error::new_spanned(
+                key,
+                "expected `component = <ident>`",
+            ));
+        }
+
+        input.parse::<Token![=]>()?;
+        let component: Ident = input.parse()?;
+
+        let content;
+        braced!(content in input);
+        let pipeline: PipelineAst = content.parse()?;
+
+        Ok(Self {
+            component,
+            pipeline,
+        })
+    }
+}
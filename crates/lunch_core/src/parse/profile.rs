@@ -0,0 +1,65 @@
+use syn::{
+    Ident, Token, braced,
+    parse::{Parse, ParseStream, Result},
+};
+
+use crate::diagnostics::{Diagnostics, parse_until};
+use crate::parse::{
+    clinch::{ScoreValueAst, SignalPath},
+    kw,
+};
+
+/// `profile email { score risk for signal.format.pe_magic = 6.0 }`
+pub struct ProfileBlock {
+    pub name: Ident,
+    pub overrides: Vec<ScoreOverride>,
+}
+
+/// Replaces the value of every `score <key>` action in the clauses on `signal`.
+pub struct ScoreOverride {
+    pub key: Ident,
+    pub signal: SignalPath,
+    pub value: ScoreValueAst,
+}
+
+impl Parse for ProfileBlock {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name: Ident = input.parse()?;
+
+        let content;
+        braced!(content in input);
+
+        let mut diags = Diagnostics::new();
+        let mut overrides = Vec::new();
+        while !content.is_empty() {
+            if let Some(o) = parse_until(&content, &mut diags, |input| input.peek(kw::score)) {
+                overrides.push(o);
+            }
+
+            if content.peek(Token![,]) {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        diags.finish()?;
+
+        Ok(Self { name, overrides })
+    }
+}
+
+impl Parse for ScoreOverride {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if !input.peek(kw::score) {
+            return Err(input.error("expected `score <key> for signal.<family>.<name> = <value>`"));
+        }
+        input.parse::<kw::score>()?;
+
+        let key: Ident = input.parse()?;
+        input.parse::<Token![for]>()?;
+        let signal: SignalPath = input.parse()?;
+        input.parse::<Token![=]>()?;
+
+        let value = input.parse()?;
+
+        Ok(Self { key, signal, value })
+    }
+}
@@ -0,0 +1,80 @@
+use crate::diagnostics::{Diagnostics, parse_until};
+use syn::{
+    Ident, LitStr, Token, braced,
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream, Result},
+};
+
+pub struct RollupBlock {
+    pub entries: Vec<RollupEntry>,
+}
+
+/// `<target>: <rule>`. `tags` and `verdict` are reserved targets; anything else
+/// is a score key.
+pub struct RollupEntry {
+    pub target: Ident,
+    pub rule: RollupRule,
+}
+
+pub enum RollupRule {
+    /// `max(children)`, `min(children)`, `sum(children)`
+    Aggregate { func: Ident, source: Ident },
+    /// `union` or `union prefixed "<prefix>"`
+    Union { kw: Ident, prefix: Option<LitStr> },
+}
+
+impl Parse for RollupBlock {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        braced!(content in input);
+
+        let mut diags = Diagnostics::new();
+        let mut entries = Vec::new();
+        while !content.is_empty() {
+            if let Some(entry) = parse_until(&content, &mut diags, at_entry_start) {
+                entries.push(entry);
+            }
+
+            if content.peek(Token![,]) {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        diags.finish()?;
+
+        Ok(Self { entries })
+    }
+}
+
+impl Parse for RollupEntry {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let target: Ident = input.call(Ident::parse_any)?;
+        input.parse::<Token![:]>()?;
+
+        let kw: Ident = input.parse()?;
+
+        let rule = if kw == "union" {
+            let prefix = if input.peek(Ident) && input.fork().parse::<Ident>()? == "prefixed" {
+                input.parse::<Ident>()?;
+                Some(input.parse()?)
+            } else {
+                None
+            };
+
+            RollupRule::Union { kw, prefix }
+        } else {
+            let args;
+            parenthesized!(args in input);
+            let source: Ident = args.parse()?;
+
+            RollupRule::Aggregate { func: kw, source }
+        };
+
+        Ok(Self { target, rule })
+    }
+}
+
+/// `<target>:` starts the next entry; targets may be keywords.
+fn at_entry_start(input: ParseStream) -> bool {
+    input.peek(Ident::peek_any) && input.peek2(Token![:]) && !input.peek2(Token![::])
+}
@@ -0,0 +1,183 @@
+use crate::{
+    diagnostics::{Diagnostics, parse_item},
+    lints::Lint,
+    parse::{
+        attrs::{ItemAttrs, parse_item_attrs},
+        kw,
+    },
+};
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{
+    Attribute, Ident, Token, braced,
+    parse::{Parse, ParseStream, Result},
+};
+
+pub struct SignalsBlock {
+    pub families: Vec<SignalFamily>,
+}
+
+pub struct SignalFamily {
+    pub attrs: Vec<Attribute>,
+    pub allow: Vec<Lint>,
+    pub name: Ident,
+    pub signals: Vec<SignalDef>,
+}
+
+pub struct SignalDef {
+    pub attrs: Vec<Attribute>,
+    pub allow: Vec<Lint>,
+    pub name: Ident,
+    pub body: SignalBody,
+}
+
+pub struct SignalBody {
+    pub derive_from: DeriveFrom,
+    /// Rust condition over the step's binding; runs to the end of the body.
+    pub when: TokenStream2,
+}
+
+/// `derive from operation.<op>.<step>`
+pub struct DeriveFrom {
+    pub operation: Ident,
+    pub step: Ident,
+}
+
+impl Parse for SignalsBlock {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        braced!(content in input);
+
+        let mut diags = Diagnostics::new();
+        let mut families = Vec::new();
+        while !content.is_empty() {
+            if let Some(family) = parse_item(&content, &mut diags) {
+                families.push(family);
+            }
+        }
+        diags.finish()?;
+
+        Ok(Self { families })
+    }
+}
+
+impl Parse for SignalFamily {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ItemAttrs {
+            cfg: attrs, allow, ..
+        } = parse_item_attrs(input, false)?;
+        if !input.peek(kw::family) {
+            return Err(input.error("expected `family <name> { ... }`"));
+        }
+        input.parse::<kw::family>()?;
+
+        let name: Ident = input.parse()?;
+
+        let content;
+        braced!(content in input);
+
+        let mut diags = Diagnostics::new();
+        let mut signals = Vec::new();
+        while !content.is_empty() {
+            if let Some(signal) = parse_item(&content, &mut diags) {
+                signals.push(signal);
+            }
+        }
+        diags.finish()?;
+
+        Ok(Self {
+            attrs,
+            allow,
+            name,
+            signals,
+        })
+    }
+}
+
+impl Parse for SignalDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ItemAttrs {
+            cfg: attrs, allow, ..
+        } = parse_item_attrs(input, false)?;
+        if !input.peek(kw::signal) {
+            return Err(input.error("expected `signal <name> { ... }`"));
+        }
+        input.parse::<kw::signal>()?;
+
+        let name: Ident = input.parse()?;
+        let body: SignalBody = input.parse()?;
+
+        Ok(Self {
+            attrs,
+            allow,
+            name,
+            body,
+        })
+    }
+}
+
+impl Parse for SignalBody {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        braced!(content in input);
+
+        let mut derive_from = None;
+        let mut when = None;
+
+        while !content.is_empty() {
+            if content.peek(kw::derive) {
+                let kw_derive: kw::derive = content.parse()?;
+                if derive_from.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        kw_derive,
+                        "duplicate `derive from`",
+                    ));
+                }
+                derive_from = Some(content.parse()?);
+            } else if content.peek(kw::when) {
+                let kw_when: kw::when = content.parse()?;
+                if when.is_some() {
+                    return Err(syn::Error::new_spanned(kw_when, "duplicate `when` clause"));
+                }
+                let ts: TokenStream2 = content.parse()?;
+                when = Some(ts);
+            } else {
+                return Err(
+                    content.error("expected `derive from ...` or `when ...` in signal body")
+                );
+            }
+        }
+
+        Ok(Self {
+            derive_from: derive_from.ok_or_else(|| {
+                syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "signal must declare `derive from`",
+                )
+            })?,
+            when: when.ok_or_else(|| {
+                syn::Error::new(proc_macro2::Span::call_site(), "signal must declare `when`")
+            })?,
+        })
+    }
+}
+
+impl Parse for DeriveFrom {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if !input.peek(kw::from) {
+            return Err(input.error("expected `from operation.<op>.<step>`"));
+        }
+        input.parse::<kw::from>()?;
+
+        if !input.peek(kw::operation) {
+            return Err(input.error("expected `operation.<op>.<step>`"));
+        }
+        input.parse::<kw::operation>()?;
+
+        input.parse::<Token![.]>()?;
+        let operation: Ident = input.parse()?;
+        input.parse::<Token![.]>()?;
+        let step: Ident = input.parse()?;
+
+        Ok(Self { operation, step })
+    }
+}
@@ -0,0 +1,93 @@
+use syn::{
+    Ident, braced,
+    parse::{Parse, ParseStream, Result},
+};
+
+use crate::diagnostics::{Diagnostics, parse_item, skip_item};
+use crate::parse::{clinch::SignalPath, kw, operations::OperationsBlock, signals::SignalsBlock};
+
+/// `stage <name> [when signal.<family>.<name>] { operations { ... } signals { ... } }`
+///
+/// Stages run in declaration order after the top-level `operations`/`signals`.
+/// A gated stage only runs its ops when the gate signal fired.
+pub struct StageBlock {
+    pub name: Ident,
+    pub gate: Option<SignalPath>,
+    pub operations: Option<OperationsBlock>,
+    pub signals: Option<SignalsBlock>,
+}
+
+impl Parse for StageBlock {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name: Ident = input.parse()?;
+
+        let gate = if input.peek(kw::when) {
+            input.parse::<kw::when>()?;
+            Some(input.parse::<SignalPath>()?)
+        } else if input.peek(Ident) {
+            return Err(
+                input.error("expected `when signal.<family>.<name>` or `{` after stage name")
+            );
+        } else {
+            None
+        };
+
+        let content;
+        braced!(content in input);
+
+        let mut operations: Option<OperationsBlock> = None;
+        let mut signals: Option<SignalsBlock> = None;
+
+        let mut diags = Diagnostics::new();
+        while !content.is_empty() {
+            let block: Ident = content.parse()?;
+
+            match block.to_string().as_str() {
+                "operations" => {
+                    let parsed = parse_item(&content, &mut diags);
+                    if operations.is_some() {
+                        diags.push(syn::Error::new_spanned(
+                            block,
+                            "duplicate `operations` block in stage",
+                        ));
+                    } else if parsed.is_some() {
+                        operations = parsed;
+                    }
+                }
+                "signals" => {
+                    let parsed = parse_item(&content, &mut diags);
+                    if signals.is_some() {
+                        diags.push(syn::Error::new_spanned(
+                            block,
+                            "duplicate `signals` block in stage",
+                        ));
+                    } else if parsed.is_some() {
+                        signals = parsed;
+                    }
+                }
+                _ => {
+                    diags.push(syn::Error::new_spanned(
+                        block,
+                        "expected `operations` or `signals` in stage",
+                    ));
+                    skip_item(&content);
+                }
+            }
+        }
+        diags.finish()?;
+
+        if operations.is_none() && signals.is_none() {
+            return Err(syn::Error::new_spanned(
+                &name,
+                "stage must contain an `operations` or `signals` block",
+            ));
+        }
+
+        Ok(Self {
+            name,
+            gate,
+            operations,
+            signals,
+        })
+    }
+}
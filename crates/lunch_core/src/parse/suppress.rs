@@ -0,0 +1,108 @@
+use proc_macro2::{TokenStream as TokenStream2, TokenTree};
+use syn::{
+    Ident, LitStr, Token, braced,
+    ext::IdentExt,
+    parse::{Parse, ParseStream, Result},
+};
+
+use crate::{
+    diagnostics::{Diagnostics, parse_until},
+    parse::{clinch::SignalPath, kw},
+};
+
+pub struct SuppressBlock {
+    pub entries: Vec<SuppressEntry>,
+}
+
+pub enum SuppressEntry {
+    /// `signal.<family>.<name> when <expr>`
+    Signal {
+        signal: SignalPath,
+        when: TokenStream2,
+    },
+    /// `tag <key> = "<value>" for <meta key> in "<file>"`
+    Tag {
+        key: Ident,
+        value: LitStr,
+        meta_key: Ident,
+        list: LitStr,
+    },
+}
+
+impl Parse for SuppressBlock {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let content;
+        braced!(content in input);
+
+        let mut diags = Diagnostics::new();
+        let mut entries = Vec::new();
+        while !content.is_empty() {
+            if let Some(entry) = parse_until(&content, &mut diags, at_entry_start) {
+                entries.push(entry);
+            }
+        }
+        diags.finish()?;
+
+        Ok(Self { entries })
+    }
+}
+
+impl Parse for SuppressEntry {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(kw::signal) {
+            let signal: SignalPath = input.parse()?;
+
+            if !input.peek(kw::when) {
+                return Err(input.error("expected `when <condition>` after suppressed signal"));
+            }
+            input.parse::<kw::when>()?;
+
+            // The condition runs until the next suppression entry.
+            let mut when = Vec::new();
+            while !input.is_empty() && !at_entry_start(input) {
+                when.push(input.parse::<TokenTree>()?);
+            }
+
+            Ok(SuppressEntry::Signal {
+                signal,
+                when: when.into_iter().collect(),
+            })
+        } else if input.peek(kw::tag) {
+            input.parse::<kw::tag>()?;
+            // tag keys are often keywords (`type`)
+            let key: Ident = input.call(Ident::parse_any)?;
+            input.parse::<Token![=]>()?;
+            let value: LitStr = input.parse()?;
+
+            input.parse::<Token![for]>()?;
+            let meta_key: Ident = input.parse()?;
+            input.parse::<Token![in]>()?;
+            let list: LitStr = input.parse()?;
+
+            Ok(SuppressEntry::Tag {
+                key,
+                value,
+                meta_key,
+                list,
+            })
+        } else {
+            Err(input.error(
+                "expected `signal.<family>.<name> when ...` or `tag <key> = \"<value>\" for <meta> in \"<file>\"`",
+            ))
+        }
+    }
+}
+
+/// `signal.` or `tag <ident>` starts the next entry.
+fn at_entry_start(input: ParseStream) -> bool {
+    if input.peek(kw::signal) {
+        return input.peek2(Token![.]);
+    }
+
+    // `peek2(Ident)` skips keywords, and tag keys are often keywords (`type`)
+    input.peek(kw::tag)
+        && input
+            .cursor()
+            .ident()
+            .is_some_and(|(_, rest)| rest.ident().is_some())
+}
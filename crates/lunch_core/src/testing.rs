@@ -0,0 +1,39 @@
+/*
+
+Helpers for the unit tests: run a pipeline written as a string through the same
+front end `lunch!` uses.
+
+*/
+
+use crate::{LunchGenInput, PipelineIndex, lints::lint_pipeline};
+
+pub fn parse(src: &str) -> LunchGenInput {
+    syn::parse_str(src).expect("test pipeline parses")
+}
+
+pub fn index(src: &str) -> PipelineIndex {
+    let input = parse(src);
+    match input.pipeline.validate_and_index(&input.component) {
+        Ok(index) => index,
+        Err(err) => panic!("test pipeline is invalid: {}", err),
+    }
+}
+
+/// Every validation error, in the order they were reported.
+pub fn errors(src: &str) -> Vec<String> {
+    let input = parse(src);
+    match input.pipeline.validate_and_index(&input.component) {
+        Ok(_) => Vec::new(),
+        Err(err) => err.into_iter().map(|err| err.to_string()).collect(),
+    }
+}
+
+/// Every lint warning as `<lint>: <message>`.
+pub fn warnings(src: &str) -> Vec<String> {
+    let input = parse(src);
+    let index = index(src);
+    lint_pipeline(&input.pipeline, &index)
+        .into_iter()
+        .map(|warning| format!("{}: {}", warning.lint.as_str(), warning.message))
+        .collect()
+}
@@ -0,0 +1,121 @@
+/*
+
+`#[cfg(...)]` consistency. Whatever references a cfg'd item has to carry the same
+attributes, otherwise the lowered code names a variable that was compiled out.
+Attributes are compared by their tokens.
+
+*/
+
+use quote::ToTokens;
+use syn::{Attribute, Error};
+
+use crate::{
+    diagnostics::Diagnostics,
+    index::{pipeline::PipelineIndex, signals::SignalInfo},
+    parse::{pipeline::PipelineAst, suppress::SuppressEntry},
+};
+
+pub fn validate_cfg(ast: &PipelineAst, index: &PipelineIndex, diags: &mut Diagnostics) {
+    // signal -> operation
+    for (_, signals_block) in ast.signal_blocks() {
+        for family in &signals_block.families {
+            for signal in &family.signals {
+                let derive_from = &signal.body.derive_from;
+                let Some(op) = index
+                    .operation_index
+                    .index
+                    .get(&derive_from.operation.to_string())
+                else {
+                    continue;
+                };
+
+                let have: Vec<&Attribute> = family.attrs.iter().chain(&signal.attrs).collect();
+                if let Some(missing) = missing_cfg(&op.cfg, &have) {
+                    diags.push(Error::new_spanned(
+                        &derive_from.operation,
+                        format!(
+                            "operation `{}` is only compiled with `{}`; add it to signal `{}` or its family",
+                            derive_from.operation, missing, signal.name
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    // clinch clause -> signal
+    if let Some(clinch) = &ast.clinch {
+        for clause in &clinch.clauses {
+            let Some(signal) = lookup(index, &clause.signal.family, &clause.signal.name) else {
+                continue;
+            };
+
+            let have: Vec<&Attribute> = clause.attrs.iter().collect();
+            if let Some(missing) = missing_cfg(&signal.cfg, &have) {
+                diags.push(Error::new_spanned(
+                    &clause.signal.name,
+                    format!(
+                        "`signal.{}.{}` is only compiled with `{}`; add it to this clause",
+                        clause.signal.family, clause.signal.name, missing
+                    ),
+                ));
+            }
+        }
+    }
+
+    // stage gates and suppressions can't carry attributes
+    let unconditional = ast
+        .stages
+        .iter()
+        .filter_map(|stage| stage.gate.as_ref())
+        .chain(
+            ast.suppress
+                .iter()
+                .flat_map(|s| &s.entries)
+                .filter_map(|entry| match entry {
+                    SuppressEntry::Signal { signal, .. } => Some(signal),
+                    SuppressEntry::Tag { .. } => None,
+                }),
+        );
+
+    for path in unconditional {
+        if let Some(signal) = lookup(index, &path.family, &path.name)
+            && let Some(attr) = signal.cfg.first()
+        {
+            diags.push(Error::new_spanned(
+                &path.name,
+                format!(
+                    "`signal.{}.{}` is only compiled with `{}` and can't be used here",
+                    path.family,
+                    path.name,
+                    attr.to_token_stream()
+                ),
+            ));
+        }
+    }
+}
+
+fn lookup<'a>(
+    index: &'a PipelineIndex,
+    family: &syn::Ident,
+    name: &syn::Ident,
+) -> Option<&'a SignalInfo> {
+    index
+        .signal_index
+        .families
+        .get(&family.to_string())
+        .and_then(|f| f.signals.get(&name.to_string()))
+}
+
+/// First attribute in `required` that `have` lacks, rendered for the error.
+fn missing_cfg(required: &[Attribute], have: &[&Attribute]) -> Option<String> {
+    let have: Vec<String> = have
+        .iter()
+        .map(|attr| attr.to_token_stream().to_string())
+        .collect();
+
+    required
+        .iter()
+        .map(|attr| attr.to_token_stream().to_string())
+        .find(|attr| !have.contains(attr))
+}
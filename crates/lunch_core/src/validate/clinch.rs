@@ -0,0 +1,334 @@
+use crate::diagnostics::Diagnostics;
+use crate::index::{
+    clinch::{Severity, TagValue, VerdictLevel, parse_tag_template, score_value},
+    operations::OperationIndex,
+    params::{ParamIndex, ParamType},
+    signals::{SignalIndex, SignalInfo},
+};
+
+use crate::{
+    parse::{
+        attrs::WinsKind,
+        clinch::{
+            BindingPath, ClinchActionAst, ClinchBlock, ClinchBody, EmitPayload, RuleMeta,
+            ScoreValueAst, SignalPath, TagValueAst,
+        },
+        pipeline::PipelineAst,
+    },
+    validate::core_validate::{
+        parse_expr, suggest, validate_binding_references, validate_unique_names,
+    },
+};
+use quote::ToTokens;
+use std::collections::HashMap;
+use syn::{Error, LitStr, Result};
+
+pub fn validate_clinch(
+    ast: &PipelineAst,
+    component: &syn::Ident,
+    signal_index: &SignalIndex,
+    op_index: &OperationIndex,
+    params: &ParamIndex,
+    diags: &mut Diagnostics,
+) -> Result<()> {
+    let Some(clinch_block) = ast.clinch.as_ref() else {
+        return Err(diags.bail(Error::new(
+            proc_macro2::Span::call_site(),
+            "missing `clinch { ... }` block",
+        )));
+    };
+
+    diags.check(validate_unique_names(
+        clinch_block
+            .clauses
+            .iter()
+            .filter_map(|clause| clause.rule.as_ref().map(|rule| rule.id.clone())),
+        "rule",
+    ));
+
+    for clause in &clinch_block.clauses {
+        if let Some(rule) = &clause.rule {
+            diags.check(validate_rule_meta(rule));
+        }
+
+        diags.check(resolve_signal(&clause.signal, signal_index));
+        diags.check(validate_actions_non_empty(&clause.body));
+
+        let binding = signal_binding(&clause.signal, signal_index, op_index);
+        validate_actions(&clause.body, binding, component, params, diags);
+    }
+
+    validate_score_wins(clinch_block, diags);
+
+    Ok(())
+}
+
+/// `#[lunch::set_wins(key)]` goes on a clause that writes `score key`, and a key
+/// can't be declared both ways.
+fn validate_score_wins(clinch_block: &ClinchBlock, diags: &mut Diagnostics) {
+    let mut declared: HashMap<String, WinsKind> = HashMap::new();
+
+    for clause in &clinch_block.clauses {
+        for wins in &clause.wins {
+            let key = wins.key.to_string();
+
+            let writes_key =
+                clause.body.actions.iter().any(
+                    |action| matches!(action, ClinchActionAst::Score { key: k, .. } if *k == key),
+                );
+            if !writes_key {
+                diags.push(Error::new_spanned(
+                    &wins.key,
+                    format!(
+                        "`{}` annotates `score {}`, but this clause doesn't write it",
+                        wins.kind.as_str(),
+                        key
+                    ),
+                ));
+                continue;
+            }
+
+            if let Some(kind) = declared.insert(key.clone(), wins.kind)
+                && kind != wins.kind
+            {
+                diags.push(Error::new_spanned(
+                    &wins.key,
+                    format!(
+                        "`score {}` is declared both `set_wins` and `accumulate_wins`",
+                        key
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn validate_rule_meta(rule: &RuleMeta) -> Result<()> {
+    if rule.title.value().trim().is_empty() {
+        return Err(Error::new_spanned(
+            &rule.title,
+            "rule title must not be empty",
+        ));
+    }
+
+    if Severity::from_ident(&rule.severity.to_string()).is_none() {
+        return Err(Error::new_spanned(
+            &rule.severity,
+            "expected severity `low`, `medium`, `high` or `critical`",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Resolve `signal.<family>.<name>`, suggesting the closest family or signal
+/// when either is unknown.
+pub fn resolve_signal<'a>(signal: &SignalPath, index: &'a SignalIndex) -> Result<&'a SignalInfo> {
+    let family_name = signal.family.to_string();
+    let family = index.families.get(&family_name).ok_or_else(|| {
+        Error::new_spanned(
+            &signal.family,
+            format!(
+                "unknown signal family `{}`{}",
+                family_name,
+                suggest(&family_name, index.families.keys().map(String::as_str))
+            ),
+        )
+    })?;
+
+    let name = signal.name.to_string();
+    family.signals.get(&name).ok_or_else(|| {
+        Error::new_spanned(
+            &signal.name,
+            format!(
+                "unknown signal `{}` in family `{}`{}",
+                name,
+                family_name,
+                suggest(&name, family.signals.keys().map(String::as_str))
+            ),
+        )
+    })
+}
+
+/// Output binding of the step the clause's signal derives from. `None` when the
+/// signal or its step is unknown; that is reported on its own, so binding
+/// references are not checked against it.
+pub fn signal_binding<'a>(
+    signal: &SignalPath,
+    signal_index: &SignalIndex,
+    op_index: &'a OperationIndex,
+) -> Option<&'a str> {
+    signal_index
+        .families
+        .get(&signal.family.to_string())
+        .and_then(|family| family.signals.get(&signal.name.to_string()))
+        .and_then(|sig| {
+            op_index
+                .index
+                .get(&sig.derives_from.operation)
+                .and_then(|op| op.steps.get(&sig.derives_from.step))
+        })
+        .map(|step| step.binding.as_str())
+}
+
+fn validate_actions_non_empty(body: &ClinchBody) -> Result<()> {
+    if body.actions.is_empty() {
+        return Err(Error::new(
+            body.brace.span.join(),
+            "clinch body must contain at least one action",
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_actions(
+    body: &ClinchBody,
+    binding: Option<&str>,
+    component: &syn::Ident,
+    params: &ParamIndex,
+    diags: &mut Diagnostics,
+) {
+    for action in &body.actions {
+        diags.check(validate_action(action, binding, component, params));
+    }
+}
+
+fn validate_action(
+    action: &ClinchActionAst,
+    binding: Option<&str>,
+    component: &syn::Ident,
+    params: &ParamIndex,
+) -> Result<()> {
+    match action {
+        ClinchActionAst::Emit { payload, .. } => {
+            if let Some(payload) = payload {
+                validate_emit_payload(payload, binding, params)?;
+            }
+        }
+        ClinchActionAst::RunPipeline {
+            component: target, ..
+        } if target == component => {
+            return Err(Error::new_spanned(
+                target,
+                format!("pipeline `{}` cannot run itself", component),
+            ));
+        }
+        ClinchActionAst::Verdict { level } => {
+            if VerdictLevel::from_ident(&level.to_string()).is_none() {
+                return Err(Error::new_spanned(
+                    level,
+                    "expected `verdict malicious`, `verdict suspicious` or `verdict clean`",
+                ));
+            }
+        }
+        ClinchActionAst::Tag { value, .. } => match value {
+            TagValueAst::Literal(lit) => validate_tag_literal(lit, binding, params)?,
+            TagValueAst::Binding(path) => validate_tag_binding(path, binding, params)?,
+        },
+        ClinchActionAst::Score { value, .. } => validate_score_value(value, params, "score")?,
+        ClinchActionAst::RunPipeline { .. }
+        | ClinchActionAst::RunDeferred { .. }
+        | ClinchActionAst::Halt => {}
+    }
+
+    Ok(())
+}
+
+/// A number literal, optionally negative, or an `f64` param. Shared with `profile`.
+pub fn validate_score_value(value: &ScoreValueAst, params: &ParamIndex, site: &str) -> Result<()> {
+    match value {
+        ScoreValueAst::Param(param) => match params.get(&param.to_string()) {
+            Some(info) if info.ty == ParamType::F64 => Ok(()),
+            Some(info) => Err(Error::new_spanned(
+                param,
+                format!(
+                    "param `{}` is `{}`; scores need an `f64` param",
+                    param,
+                    info.ty.as_str()
+                ),
+            )),
+            None => {
+                let name = param.to_string();
+                Err(Error::new_spanned(
+                    param,
+                    format!(
+                        "unknown param `{}` in {}{}",
+                        name,
+                        site,
+                        suggest(&name, params.params.iter().map(|p| p.name.as_str()))
+                    ),
+                ))
+            }
+        },
+        ScoreValueAst::Literal { lit, .. } => match score_value(value) {
+            Some(_) => Ok(()),
+            None => Err(Error::new_spanned(lit, "score value must be a number")),
+        },
+    }
+}
+
+fn validate_tag_literal(lit: &LitStr, binding: Option<&str>, params: &ParamIndex) -> Result<()> {
+    if let TagValue::Interpolated { args, .. } = parse_tag_template(lit)?
+        && let Some(binding) = binding
+    {
+        for arg in args {
+            parse_expr(&arg, "tag interpolation")
+                .and_then(|arg| {
+                    validate_binding_references(&arg, &[binding], params, "tag interpolation")
+                })
+                .map_err(|e| Error::new_spanned(lit, e.to_string()))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_tag_binding(
+    path: &BindingPath,
+    binding: Option<&str>,
+    params: &ParamIndex,
+) -> Result<()> {
+    let Some(binding) = binding else {
+        return Ok(());
+    };
+
+    // `<binding>` alone has no `.` so it would slip past the reference check.
+    if path.fields.is_empty() {
+        return Err(Error::new_spanned(
+            path,
+            format!("expected `{}.<field>` as tag value", binding),
+        ));
+    }
+
+    let value = parse_expr(&path.to_token_stream(), "tag value")?;
+    validate_binding_references(&value, &[binding], params, "tag value")
+}
+
+fn validate_emit_payload(
+    payload: &EmitPayload,
+    binding: Option<&str>,
+    params: &ParamIndex,
+) -> Result<()> {
+    if payload.fields.is_empty() {
+        return Err(Error::new(
+            payload.brace.span.join(),
+            "emit payload must declare at least one field",
+        ));
+    }
+
+    validate_unique_names(
+        payload.fields.iter().map(|field| &field.name),
+        "emit payload field",
+    )?;
+
+    let Some(binding) = binding else {
+        return Ok(());
+    };
+
+    for field in &payload.fields {
+        validate_binding_references(&field.value, &[binding], params, "emit payload")?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,188 @@
+use proc_macro2::TokenStream as TokenStream2;
+use std::collections::HashSet;
+use syn::{
+    Error, Expr, ExprClosure, ExprField, ExprMethodCall, ExprPath, PatIdent, Result,
+    visit::{self, Visit},
+};
+
+use crate::{diagnostics::Diagnostics, index::params::ParamIndex};
+
+pub fn validate_unique_names<I, T>(items: I, kind: &str) -> Result<()>
+where
+    I: IntoIterator<Item = T>,
+    T: quote::ToTokens + std::fmt::Display,
+{
+    let mut seen = HashSet::new();
+    let mut diags = Diagnostics::new();
+
+    for item in items {
+        let name = item.to_string();
+        if !seen.insert(name.clone()) {
+            diags.push(Error::new_spanned(
+                item,
+                format!("duplicate {} name `{}`", kind, name),
+            ));
+        }
+    }
+
+    diags.finish()
+}
+/// Tail for an "unknown name" error: `; did you mean `x`?` when one of
+/// `candidates` is a small edit away from `name`, otherwise the full list of
+/// valid names. Empty when there is nothing to offer.
+pub fn suggest<'a, I>(name: &str, candidates: I) -> String
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut candidates: Vec<&str> = candidates.into_iter().collect();
+    // index maps iterate in hash order; sort so the message is stable
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    // Same cutoff rustc uses for its own suggestions.
+    let max_distance = (name.chars().count() / 3).max(1);
+    let closest = candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min();
+
+    match (closest, candidates.as_slice()) {
+        (Some((_, candidate)), _) => format!("; did you mean `{}`?", candidate),
+        (None, []) => String::new(),
+        (None, [only]) => format!("; expected `{}`", only),
+        (None, all) => format!(
+            "; expected one of {}",
+            all.iter()
+                .map(|c| format!("`{}`", c))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// `ts` as one Rust expression, the way a `when` condition is lowered.
+pub fn parse_expr(ts: &TokenStream2, site: &str) -> Result<Expr> {
+    syn::parse2(ts.clone()).map_err(|err| Error::new(err.span(), format!("{} in {}", err, site)))
+}
+
+/// Every bare name a field access or method call starts from must be one of
+/// `allowed`, normally the output binding of the step the signal derives from,
+/// or a param. Closure arguments count inside their closure. Fields and method
+/// calls further down the chain are left to the Rust compiler.
+pub fn validate_binding_references(
+    expr: &Expr,
+    allowed: &[&str],
+    params: &ParamIndex,
+    site: &str,
+) -> Result<()> {
+    let mut references = BindingReferences {
+        allowed: allowed
+            .iter()
+            .copied()
+            .chain(params.params.iter().map(|param| param.name.as_str()))
+            .collect(),
+        locals: Vec::new(),
+        site,
+        error: None,
+    };
+    references.visit_expr(expr);
+
+    references.error.map_or(Ok(()), Err)
+}
+
+struct BindingReferences<'a> {
+    allowed: Vec<&'a str>,
+    /// Closure arguments in scope at the current node.
+    locals: Vec<String>,
+    site: &'a str,
+    /// The first unknown name; later ones are not reported.
+    error: Option<Error>,
+}
+
+impl BindingReferences<'_> {
+    fn check_receiver(&mut self, receiver: &Expr) {
+        let Expr::Path(ExprPath {
+            qself: None, path, ..
+        }) = receiver
+        else {
+            return;
+        };
+        let Some(ident) = path.get_ident() else {
+            return;
+        };
+
+        let name = ident.to_string();
+        if self.error.is_some()
+            || self.allowed.contains(&name.as_str())
+            || self.locals.contains(&name)
+        {
+            return;
+        }
+
+        self.error = Some(Error::new_spanned(
+            ident,
+            format!(
+                "unknown binding `{}` in {}{}",
+                name,
+                self.site,
+                suggest(&name, self.allowed.iter().copied())
+            ),
+        ));
+    }
+}
+
+impl<'ast> Visit<'ast> for BindingReferences<'_> {
+    fn visit_expr_field(&mut self, node: &'ast ExprField) {
+        self.check_receiver(&node.base);
+        visit::visit_expr_field(self, node);
+    }
+
+    fn visit_expr_method_call(&mut self, node: &'ast ExprMethodCall) {
+        self.check_receiver(&node.receiver);
+        visit::visit_expr_method_call(self, node);
+    }
+
+    fn visit_expr_closure(&mut self, node: &'ast ExprClosure) {
+        let scope = self.locals.len();
+        for input in &node.inputs {
+            PatNames(&mut self.locals).visit_pat(input);
+        }
+
+        visit::visit_expr_closure(self, node);
+        self.locals.truncate(scope);
+    }
+}
+
+/// Names bound by a closure argument pattern.
+struct PatNames<'a>(&'a mut Vec<String>);
+
+impl<'ast> Visit<'ast> for PatNames<'_> {
+    fn visit_pat_ident(&mut self, node: &'ast PatIdent) {
+        self.0.push(node.ident.to_string());
+        visit::visit_pat_ident(self, node);
+    }
+}
@@ -0,0 +1,155 @@
+pub mod cfg;
+pub mod clinch;
+pub mod core_validate;
+pub mod operations;
+pub mod params;
+pub mod profiles;
+pub mod rollup;
+pub mod signals;
+pub mod suppress;
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::errors;
+
+    fn pipeline(signals: &str, rest: &str) -> String {
+        format!(
+            r#"component = demo {{
+                params {{ HIGH_ENTROPY: f64 = 7.0 }}
+                operations {{
+                    operation magic {{
+                        do inspect_magic output magic_probe
+                        do entropy_probe output entropy_probe
+                    }}
+                }}
+                signals {{
+                    family format {{
+                        {signals}
+                    }}
+                }}
+                {rest}
+            }}"#
+        )
+    }
+
+    const PDF: &str = "signal pdf_magic {
+        derive from operation.magic.inspect_magic
+            when magic_probe.magic == [0x25, 0x50, 0x44, 0x46]
+    }";
+
+    const CLINCH: &str = "clinch { when signal.format.pdf_magic { score risk += 1.0 } }";
+
+    fn signal(derive: &str, when: &str) -> String {
+        format!("signal probe {{ derive from {derive} when {when} }}")
+    }
+
+    #[test]
+    fn valid_pipeline_has_no_errors() {
+        assert_eq!(errors(&pipeline(PDF, CLINCH)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unknown_operation_suggests_close_match() {
+        let signals = signal("operation.magik.inspect_magic", "magic_probe.matched");
+        let clinch = "clinch { when signal.format.probe { score risk += 1.0 } }";
+
+        assert_eq!(
+            errors(&pipeline(&signals, clinch)),
+            ["operation `magik` does not exist; did you mean `magic`?"]
+        );
+    }
+
+    #[test]
+    fn unknown_step_suggests_close_match() {
+        let signals = signal("operation.magic.inspect_magik", "magic_probe.matched");
+        let clinch = "clinch { when signal.format.probe { score risk += 1.0 } }";
+
+        assert_eq!(
+            errors(&pipeline(&signals, clinch)),
+            [
+                "step `inspect_magik` does not exist on operation `magic`; did you mean `inspect_magic`?"
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_signal_suggests_close_match() {
+        let clinch = "clinch { when signal.format.pdf_magik { score risk += 1.0 } }";
+
+        assert_eq!(
+            errors(&pipeline(PDF, clinch)),
+            ["unknown signal `pdf_magik` in family `format`; did you mean `pdf_magic`?"]
+        );
+    }
+
+    #[test]
+    fn unknown_family_lists_every_family_when_nothing_is_close() {
+        let clinch = "clinch { when signal.structural.pdf_magic { score risk += 1.0 } }";
+
+        assert_eq!(
+            errors(&pipeline(PDF, clinch)),
+            ["unknown signal family `structural`; expected `format`"]
+        );
+    }
+
+    #[test]
+    fn params_are_accepted_as_receivers() {
+        let signals = signal(
+            "operation.magic.entropy_probe",
+            "entropy_probe.entropy > (HIGH_ENTROPY).max(1.0) && HIGH_ENTROPY.is_finite()",
+        );
+        let clinch = "clinch { when signal.format.probe { score risk += 1.0 } }";
+
+        assert_eq!(errors(&pipeline(&signals, clinch)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn closure_arguments_are_accepted_as_receivers() {
+        let signals = signal(
+            "operation.magic.inspect_magic",
+            "magic_probe.magic.iter().any(|byte| byte.is_ascii_digit())",
+        );
+        let clinch = "clinch { when signal.format.probe { score risk += 1.0 } }";
+
+        assert_eq!(errors(&pipeline(&signals, clinch)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unknown_receiver_in_when_suggests_binding() {
+        let signals = signal("operation.magic.inspect_magic", "magic_prob.matched");
+        let clinch = "clinch { when signal.format.probe { score risk += 1.0 } }";
+
+        assert_eq!(
+            errors(&pipeline(&signals, clinch)),
+            ["unknown binding `magic_prob` in `when`; did you mean `magic_probe`?"]
+        );
+    }
+
+    #[test]
+    fn emit_payload_only_reads_the_clause_binding() {
+        let clinch = "clinch {
+            when signal.format.pdf_magic {
+                emit Emit::Pdf { entropy = entropy_probe.entropy }
+            }
+        }";
+
+        assert_eq!(
+            errors(&pipeline(PDF, clinch)),
+            [
+                "unknown binding `entropy_probe` in emit payload; expected one of `HIGH_ENTROPY`, `magic_probe`"
+            ]
+        );
+    }
+
+    #[test]
+    fn suppression_may_index_meta() {
+        let rest = format!(
+            r#"suppress {{
+                signal.format.pdf_magic when artifact.meta["signer"] == "Contoso"
+            }}
+            {CLINCH}"#
+        );
+
+        assert_eq!(errors(&pipeline(PDF, &rest)), Vec::<String>::new());
+    }
+}
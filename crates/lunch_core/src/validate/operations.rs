@@ -0,0 +1,50 @@
+use syn::{Error, Result};
+
+use crate::diagnostics::Diagnostics;
+use crate::parse::{operations::OperationsBlock, pipeline::PipelineAst};
+use crate::validate::core_validate::validate_unique_names;
+
+pub fn validate_operations(ast: &PipelineAst, diags: &mut Diagnostics) -> Result<()> {
+    if ast.operation_blocks().next().is_none() {
+        return Err(diags.bail(Error::new(
+            proc_macro2::Span::call_site(),
+            "missing `operations { ... }` block",
+        )));
+    }
+
+    // Operation names are shared across stages.
+    diags.check(validate_unique_names(
+        ast.operation_blocks()
+            .flat_map(|(_, ops)| ops.definitions.iter().map(|op| op.name.clone())),
+        "operation",
+    ));
+
+    for (_, ops) in ast.operation_blocks() {
+        validate_operation_bodies(ops, diags);
+    }
+
+    Ok(())
+}
+
+/// The grammar only admits `do` statements, so there is no room for loops,
+/// threads or `&mut Context` in an operation body; what's left is naming.
+fn validate_operation_bodies(ops: &OperationsBlock, diags: &mut Diagnostics) {
+    for op in &ops.definitions {
+        if op.body.stmts.is_empty() {
+            diags.push(Error::new_spanned(
+                &op.name,
+                "operations must declare at least one `do` statement",
+            ));
+            continue;
+        }
+
+        diags.check(validate_unique_names(
+            op.body.stmts.iter().map(|stmt| &stmt.step),
+            "step",
+        ));
+        diags.check(validate_unique_names(
+            op.body.stmts.iter().map(|stmt| &stmt.output),
+            "output",
+        ));
+    }
+}
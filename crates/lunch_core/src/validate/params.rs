@@ -0,0 +1,52 @@
+use crate::{
+    diagnostics::Diagnostics,
+    index::params::{ParamType, ParamValue},
+    parse::{params::ParamDef, pipeline::PipelineAst},
+    validate::core_validate::validate_unique_names,
+};
+use syn::{Error, Result};
+
+/// `params` is optional; each entry needs a supported type and a literal of that type.
+pub fn validate_params(ast: &PipelineAst, diags: &mut Diagnostics) {
+    let Some(params_block) = ast.params.as_ref() else {
+        return;
+    };
+
+    diags.check(validate_unique_names(
+        params_block.params.iter().map(|param| param.name.clone()),
+        "param",
+    ));
+
+    for param in &params_block.params {
+        diags.check(validate_param(param));
+    }
+}
+
+fn validate_param(param: &ParamDef) -> Result<()> {
+    if param.name == "disabled" || param.name == "profile" {
+        return Err(Error::new_spanned(
+            &param.name,
+            format!("`{}` is a reserved key in config files", param.name),
+        ));
+    }
+
+    let ty = ParamType::from_ident(&param.ty.to_string()).ok_or_else(|| {
+        Error::new_spanned(
+            &param.ty,
+            "expected param type `f64`, `i64`, `u64`, `usize` or `bool`",
+        )
+    })?;
+
+    if ParamValue::from_def(param, ty).is_none() {
+        let message = match (&param.negative, ty) {
+            (Some(_), ParamType::U64 | ParamType::Usize | ParamType::Bool) => {
+                format!("`{}` param cannot be negative", ty.as_str())
+            }
+            _ => format!("expected a `{}` literal", ty.as_str()),
+        };
+
+        return Err(Error::new_spanned(&param.value, message));
+    }
+
+    Ok(())
+}
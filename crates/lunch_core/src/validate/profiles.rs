@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+
+use crate::{
+    diagnostics::Diagnostics,
+    index::{
+        clinch::{ClinchAction, ClinchIndex},
+        params::ParamIndex,
+    },
+    parse::{
+        pipeline::PipelineAst,
+        profile::{ProfileBlock, ScoreOverride},
+    },
+    validate::{
+        clinch::validate_score_value,
+        core_validate::{suggest, validate_unique_names},
+    },
+};
+use syn::{Error, Result};
+
+/// Profile names that would collide with the generated `run_<component>_pipeline_*` fns.
+const RESERVED_PROFILES: &[&str] = &["explained", "with", "in", "in_with"];
+
+/// Each override must hit a `score <key>` action that exists in the clinch block.
+pub fn validate_profiles(
+    ast: &PipelineAst,
+    clinch: &ClinchIndex,
+    params: &ParamIndex,
+    diags: &mut Diagnostics,
+) {
+    diags.check(validate_unique_names(
+        ast.profiles.iter().map(|p| p.name.clone()),
+        "profile",
+    ));
+
+    // Profiles become variants of `<Component>Profile` next to `Default`, so
+    // `e_mail`/`email` or `_default` would clash once camel-cased.
+    let mut variants = HashSet::from(["default".to_string()]);
+
+    for profile in &ast.profiles {
+        let name = profile.name.to_string();
+        if RESERVED_PROFILES.contains(&name.as_str()) {
+            diags.push(Error::new_spanned(
+                &profile.name,
+                format!("`{}` is reserved and cannot name a profile", name),
+            ));
+        } else if !variants.insert(name.to_lowercase().replace('_', "")) {
+            diags.push(Error::new_spanned(
+                &profile.name,
+                format!(
+                    "profile `{}` clashes with another profile or `default` in the generated profile enum",
+                    name
+                ),
+            ));
+        }
+
+        let mut seen = HashSet::new();
+        for o in &profile.overrides {
+            diags.check(validate_override(profile, o, &mut seen, clinch, params));
+        }
+    }
+}
+
+fn validate_override(
+    profile: &ProfileBlock,
+    o: &ScoreOverride,
+    seen: &mut HashSet<(String, String, String)>,
+    clinch: &ClinchIndex,
+    params: &ParamIndex,
+) -> Result<()> {
+    let (family, name, key) = (
+        o.signal.family.to_string(),
+        o.signal.name.to_string(),
+        o.key.to_string(),
+    );
+
+    if !seen.insert((family.clone(), name.clone(), key.clone())) {
+        return Err(Error::new_spanned(
+            &o.key,
+            format!(
+                "duplicate override of `score {}` for signal.{}.{} in profile `{}`",
+                key, family, name, profile.name
+            ),
+        ));
+    }
+
+    let on_signal: Vec<_> = clinch
+        .clauses
+        .iter()
+        .filter(|c| c.signal.family == family && c.signal.name == name)
+        .collect();
+
+    if on_signal.is_empty() {
+        let path = format!("signal.{}.{}", family, name);
+        let clause_signals: Vec<String> = clinch
+            .clauses
+            .iter()
+            .map(|c| format!("signal.{}.{}", c.signal.family, c.signal.name))
+            .collect();
+
+        return Err(Error::new_spanned(
+            &o.signal.name,
+            format!(
+                "no clinch clause on {}{}",
+                path,
+                suggest(&path, clause_signals.iter().map(String::as_str))
+            ),
+        ));
+    }
+
+    let score_keys: Vec<&str> = on_signal
+        .iter()
+        .flat_map(|c| &c.actions)
+        .filter_map(|action| match action {
+            ClinchAction::Score { key, .. } => Some(key.as_str()),
+            _ => None,
+        })
+        .collect();
+    if !score_keys.contains(&key.as_str()) {
+        return Err(Error::new_spanned(
+            &o.key,
+            format!(
+                "no clinch clause on signal.{}.{} writes `score {}`{}",
+                family,
+                name,
+                key,
+                suggest(&key, score_keys)
+            ),
+        ));
+    }
+
+    validate_score_value(&o.value, params, "profile")
+}
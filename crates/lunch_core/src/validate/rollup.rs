@@ -0,0 +1,85 @@
+use crate::{
+    diagnostics::Diagnostics,
+    index::{operations::OperationIndex, rollup::AggregateFn},
+    parse::{
+        pipeline::PipelineAst,
+        rollup::{RollupEntry, RollupRule},
+    },
+    validate::core_validate::validate_unique_names,
+};
+use syn::{Error, Result};
+
+/// `rollup` is optional; when present the pipeline must have something to roll up.
+pub fn validate_rollup(ast: &PipelineAst, op_index: &OperationIndex, diags: &mut Diagnostics) {
+    let Some(rollup_block) = ast.rollup.as_ref() else {
+        return;
+    };
+
+    let has_children = op_index
+        .index
+        .values()
+        .flat_map(|op| op.steps.values())
+        .any(|step| step.children);
+
+    if !has_children && let Some(first) = rollup_block.entries.first() {
+        diags.push(Error::new_spanned(
+            &first.target,
+            "`rollup` needs at least one `do <step> output <name> children` step",
+        ));
+    }
+
+    diags.check(validate_unique_names(
+        rollup_block
+            .entries
+            .iter()
+            .map(|entry| entry.target.clone()),
+        "rollup target",
+    ));
+
+    for entry in &rollup_block.entries {
+        diags.check(validate_entry(entry));
+    }
+}
+
+fn validate_entry(entry: &RollupEntry) -> Result<()> {
+    let target = entry.target.to_string();
+
+    match (&entry.rule, target.as_str()) {
+        (RollupRule::Union { prefix, .. }, "tags") => {
+            if let Some(prefix) = prefix
+                && prefix.value().is_empty()
+            {
+                return Err(Error::new_spanned(prefix, "tag prefix must not be empty"));
+            }
+        }
+        (RollupRule::Union { kw, .. }, _) => {
+            return Err(Error::new_spanned(
+                kw,
+                format!("`union` only applies to `tags`, not `{}`", target),
+            ));
+        }
+        (RollupRule::Aggregate { .. }, "tags") => {
+            return Err(Error::new_spanned(
+                &entry.target,
+                "expected `tags: union` or `tags: union prefixed \"<prefix>\"`",
+            ));
+        }
+        (RollupRule::Aggregate { func, source }, _) => {
+            if source != "children" {
+                return Err(Error::new_spanned(source, "expected `children`"));
+            }
+
+            match AggregateFn::from_ident(&func.to_string()) {
+                None => {
+                    return Err(Error::new_spanned(func, "expected `max`, `min` or `sum`"));
+                }
+                Some(func_kind) if target == "verdict" && func_kind != AggregateFn::Max => {
+                    return Err(Error::new_spanned(func, "verdict only rolls up with `max`"));
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,56 +1,72 @@
 use std::collections::HashMap;
 
-use crate::lunch::{
-    index::operations::{OperationIndex, OperationInfo},
+use crate::{
+    diagnostics::Diagnostics,
+    index::{
+        operations::{OperationIndex, OperationInfo},
+        params::ParamIndex,
+    },
     parse::{
         pipeline::PipelineAst,
         signals::{SignalDef, SignalsBlock},
     },
-    validate::core_validate::{flatten, validate_unique_names},
+    validate::core_validate::{
+        parse_expr, suggest, validate_binding_references, validate_unique_names,
+    },
 };
-use proc_macro2::TokenTree;
 use syn::{Error, Result};
 
-pub fn validate_signals(ast: &PipelineAst, op_index: &OperationIndex) -> Result<()> {
-    let signals_block = ast.signals.as_ref().ok_or_else(|| {
-        Error::new(
+pub fn validate_signals(
+    ast: &PipelineAst,
+    op_index: &OperationIndex,
+    params: &ParamIndex,
+    diags: &mut Diagnostics,
+) -> Result<()> {
+    if ast.signal_blocks().next().is_none() {
+        return Err(diags.bail(Error::new(
             proc_macro2::Span::call_site(),
             "missing `signals { ... }` block",
-        )
-    })?;
+        )));
+    }
 
-    validate_unique_names(
-        signals_block.families.iter().map(|f| f.name.clone()),
+    // Families are shared across stages, so a family lives in exactly one block.
+    diags.check(validate_unique_names(
+        ast.signal_blocks()
+            .flat_map(|(_, signals)| signals.families.iter().map(|f| f.name.clone())),
         "signal family",
-    )?;
-
-    validate_signal_name_unique(signals_block)?;
-    validate_family_non_empty(signals_block)?;
-
-    for family in &signals_block.families {
-        for signal in &family.signals {
-            validate_single_when(signal)?;
-            validate_derive_from(signal, &op_index.index)?;
-            validate_ops_exist(signal, &op_index.index)?;
-            validate_when_references(signal, &op_index.index)?;
+    ));
+
+    for (_, signals_block) in ast.signal_blocks() {
+        validate_signal_name_unique(signals_block, diags);
+        validate_family_non_empty(signals_block, diags);
+
+        for family in &signals_block.families {
+            for signal in &family.signals {
+                // One error per signal; the later checks assume the earlier ones passed.
+                diags.check(
+                    validate_single_when(signal)
+                        .and_then(|()| validate_derive_from(signal, &op_index.index))
+                        .and_then(|()| validate_ops_exist(signal, &op_index.index))
+                        .and_then(|()| validate_when_references(signal, &op_index.index, params)),
+                );
+            }
         }
     }
 
     Ok(())
 }
 
-fn validate_signal_name_unique(signals_block: &SignalsBlock) -> Result<()> {
+fn validate_signal_name_unique(signals_block: &SignalsBlock, diags: &mut Diagnostics) {
     for family in &signals_block.families {
-        validate_unique_names(family.signals.iter().map(|sig| sig.name.clone()), "signal")?;
+        diags.check(validate_unique_names(
+            family.signals.iter().map(|sig| sig.name.clone()),
+            "signal",
+        ));
     }
-    Ok(())
 }
 
 fn validate_single_when(signal: &SignalDef) -> Result<()> {
-    let mut tokens = Vec::new();
-    flatten(&signal.body.when, &mut tokens);
-
-    if tokens.is_empty() {
+    if signal.body.when.is_empty() {
         return Err(Error::new_spanned(
             signal.body.when.clone(),
             "empty `when` condition in signal definition",
@@ -60,16 +76,15 @@ fn validate_single_when(signal: &SignalDef) -> Result<()> {
     Ok(())
 }
 
-fn validate_family_non_empty(signals_block: &SignalsBlock) -> Result<()> {
+fn validate_family_non_empty(signals_block: &SignalsBlock, diags: &mut Diagnostics) {
     for family in &signals_block.families {
         if family.signals.is_empty() {
-            return Err(Error::new_spanned(
+            diags.push(Error::new_spanned(
                 family.name.clone(),
                 "signal family must contain at least one signal",
             ));
         }
     }
-    Ok(())
 }
 
 fn validate_ops_exist(signal: &SignalDef, op_index: &HashMap<String, OperationInfo>) -> Result<()> {
@@ -116,7 +131,11 @@ fn validate_derive_from(
     let op = op_index.get(&op_name).ok_or_else(|| {
         Error::new_spanned(
             &signal.body.derive_from.operation,
-            format!("operation `{}` does not exist", op_name),
+            format!(
+                "operation `{}` does not exist{}",
+                op_name,
+                suggest(&op_name, op_index.keys().map(String::as_str))
+            ),
         )
     })?;
 
@@ -124,8 +143,10 @@ fn validate_derive_from(
         return Err(Error::new_spanned(
             &signal.body.derive_from.step,
             format!(
-                "step `{}` does not exist on operation `{}`",
-                step_name, op_name
+                "step `{}` does not exist on operation `{}`{}",
+                step_name,
+                op_name,
+                suggest(&step_name, op.steps.keys().map(String::as_str))
             ),
         ));
     }
@@ -133,10 +154,10 @@ fn validate_derive_from(
     Ok(())
 }
 
-use proc_macro2::Delimiter;
 fn validate_when_references(
     signal: &SignalDef,
     op_index: &HashMap<String, OperationInfo>,
+    params: &ParamIndex,
 ) -> Result<()> {
     let op_name = signal.body.derive_from.operation.to_string();
     let step_name = signal.body.derive_from.step.to_string();
@@ -155,32 +176,6 @@ fn validate_when_references(
     // The ONLY allowed binding name
     let binding = &step.binding;
 
-    let mut tokens = Vec::new();
-    flatten(&signal.body.when, &mut tokens);
-
-    let mut i = 0;
-    while i + 1 < tokens.len() {
-        if let TokenTree::Ident(ident) = &tokens[i] {
-            // Look for `<ident>.`
-            if let TokenTree::Punct(p) = &tokens[i + 1] {
-                if p.as_char() == '.' {
-                    let name = ident.to_string();
-
-                    if name != *binding {
-                        return Err(Error::new_spanned(
-                            ident.clone(),
-                            format!(
-                                "unknown binding `{}` in `when`; expected `{}`",
-                                name, binding
-                            ),
-                        ));
-                    }
-                }
-            }
-        }
-
-        i += 1;
-    }
-
-    Ok(())
+    let when = parse_expr(&signal.body.when, "`when`")?;
+    validate_binding_references(&when, &[binding], params, "`when`")
 }
@@ -0,0 +1,68 @@
+use crate::{
+    diagnostics::Diagnostics,
+    index::{operations::OperationIndex, params::ParamIndex, signals::SignalIndex},
+    parse::{pipeline::PipelineAst, suppress::SuppressEntry},
+    validate::{
+        clinch::{resolve_signal, signal_binding},
+        core_validate::{parse_expr, validate_binding_references},
+    },
+};
+use syn::{Error, Result};
+
+/// `suppress` is optional; when present every entry must point at something real.
+pub fn validate_suppress(
+    ast: &PipelineAst,
+    signal_index: &SignalIndex,
+    op_index: &OperationIndex,
+    params: &ParamIndex,
+    diags: &mut Diagnostics,
+) {
+    let Some(suppress_block) = ast.suppress.as_ref() else {
+        return;
+    };
+
+    for entry in &suppress_block.entries {
+        diags.check(validate_entry(entry, signal_index, op_index, params));
+    }
+}
+
+fn validate_entry(
+    entry: &SuppressEntry,
+    signal_index: &SignalIndex,
+    op_index: &OperationIndex,
+    params: &ParamIndex,
+) -> Result<()> {
+    match entry {
+        SuppressEntry::Signal { signal, when } => {
+            resolve_signal(signal, signal_index)?;
+
+            if when.is_empty() {
+                return Err(Error::new_spanned(
+                    &signal.name,
+                    "empty `when` condition in suppression",
+                ));
+            }
+
+            if let Some(binding) = signal_binding(signal, signal_index, op_index) {
+                let when = parse_expr(when, "suppression `when`")?;
+                validate_binding_references(
+                    &when,
+                    &["artifact", binding],
+                    params,
+                    "suppression `when`",
+                )?;
+            }
+        }
+        SuppressEntry::Tag { value, list, .. } => {
+            if value.value().is_empty() {
+                return Err(Error::new_spanned(value, "suppressed tag value is empty"));
+            }
+
+            if list.value().is_empty() {
+                return Err(Error::new_spanned(list, "expected allowlist file path"));
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,348 @@
+/*
+
+One pass of the `lunch!` front end over a root file: resolve includes, parse,
+validate, index and lint. It runs on proc-macro2's fallback lexer, whose spans
+know their file, line and column. Diagnostics come out per file, and the parsed
+pipeline is boiled down to a span-free `Model`, because every span is dropped
+before the next pass.
+
+*/
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
+use lunch_core::{LunchGenInput, include::Loader, lints::lint_pipeline};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use crate::{
+    model::{Location, Model},
+    roots::{has_extension, manifest_dir},
+};
+
+pub struct Analysis {
+    /// Every file the pass looked at, so stale diagnostics get cleared too.
+    pub diagnostics: HashMap<PathBuf, Vec<Diagnostic>>,
+    /// Files the root includes, canonical.
+    pub files: Vec<PathBuf>,
+    /// `None` if the root didn't parse.
+    pub model: Option<Model>,
+}
+
+/// `root` is canonical. `overlay` holds the open documents, by canonical path.
+pub fn analyze(root: &Path, text: &str, overlay: &HashMap<PathBuf, String>) -> Analysis {
+    // Spans of the previous pass are invalid from here on. Nothing kept holds one.
+    proc_macro2::extra::invalidate_current_thread_spans();
+
+    let manifest_dir = manifest_dir(root);
+    let mut analysis = Analysis {
+        diagnostics: HashMap::from([(root.to_path_buf(), Vec::new())]),
+        files: Vec::new(),
+        model: None,
+    };
+    let mut loader = Loader::locating()
+        .in_dir(manifest_dir.clone())
+        .with_overlay(overlay.clone());
+
+    let tokens = match TokenStream::from_str(text) {
+        Ok(tokens) => tokens,
+        Err(err) => {
+            let mut locator = Locator::new(root, &manifest_dir, &loader, overlay);
+            let location = locator.locate(err.span());
+            analysis.error(location, "not valid token syntax".to_string());
+            return analysis;
+        }
+    };
+    let body = if has_extension(root, "rs") {
+        match invocation_body(tokens) {
+            Some(body) => body,
+            None => return analysis,
+        }
+    } else {
+        tokens
+    };
+
+    let resolved = loader.resolve(body);
+    analysis.files = loader
+        .files
+        .iter()
+        .map(|name| canonical(&manifest_dir.join(name)))
+        .collect();
+    for file in &analysis.files {
+        analysis.diagnostics.entry(file.clone()).or_default();
+    }
+
+    let mut locator = Locator::new(root, &manifest_dir, &loader, overlay);
+    let resolved = match resolved {
+        Ok(resolved) => resolved,
+        Err(err) => {
+            analysis.errors(err, &mut locator);
+            return analysis;
+        }
+    };
+    let input = match syn::parse2::<LunchGenInput>(resolved.clone()) {
+        Ok(input) => input,
+        Err(err) => {
+            analysis.errors(err, &mut locator);
+            return analysis;
+        }
+    };
+
+    analysis.model = Some(Model::build(&input, resolved, &manifest_dir, &mut locator));
+
+    match input.pipeline.validate_and_index(&input.component) {
+        Ok(index) => {
+            for warning in lint_pipeline(&input.pipeline, &index) {
+                let location = locator.locate(warning.span);
+                analysis.push(
+                    location,
+                    Diagnostic {
+                        severity: Some(DiagnosticSeverity::WARNING),
+                        code: Some(NumberOrString::String(warning.lint.as_str().to_string())),
+                        message: format!(
+                            "{} (`#[allow(lunch::{})]` to silence)",
+                            warning.message,
+                            warning.lint.as_str()
+                        ),
+                        ..Diagnostic::default()
+                    },
+                );
+            }
+        }
+        Err(err) => analysis.errors(err, &mut locator),
+    }
+
+    analysis
+}
+
+impl Analysis {
+    fn errors(&mut self, err: syn::Error, locator: &mut Locator) {
+        for err in err {
+            let location = locator.locate(err.span());
+            self.error(location, err.to_string());
+        }
+    }
+
+    fn error(&mut self, location: Location, message: String) {
+        self.push(
+            location,
+            Diagnostic {
+                severity: Some(DiagnosticSeverity::ERROR),
+                message,
+                ..Diagnostic::default()
+            },
+        );
+    }
+
+    fn push(&mut self, location: Location, diagnostic: Diagnostic) {
+        self.diagnostics
+            .entry(location.path)
+            .or_default()
+            .push(Diagnostic {
+                range: location.range,
+                source: Some("lunch".to_string()),
+                ..diagnostic
+            });
+    }
+}
+
+/// Spans to file positions, for the root and whatever it included.
+pub struct Locator<'a> {
+    root: PathBuf,
+    manifest_dir: &'a Path,
+    loader: &'a Loader,
+    overlay: &'a HashMap<PathBuf, String>,
+    texts: HashMap<PathBuf, String>,
+}
+
+impl<'a> Locator<'a> {
+    fn new(
+        root: &Path,
+        manifest_dir: &'a Path,
+        loader: &'a Loader,
+        overlay: &'a HashMap<PathBuf, String>,
+    ) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            manifest_dir,
+            loader,
+            overlay,
+            texts: HashMap::new(),
+        }
+    }
+
+    pub fn locate(&mut self, span: Span) -> Location {
+        let path = match self.loader.file_of(span) {
+            Some(name) => canonical(&self.manifest_dir.join(name)),
+            None => self.root.clone(),
+        };
+        let text = self.text(&path);
+        let range = Range::new(
+            position(text, span.start().line, span.start().column),
+            position(text, span.end().line, span.end().column),
+        );
+        Location { path, range }
+    }
+
+    /// The source text from the start of `tokens` to their end.
+    pub fn source(&mut self, tokens: &TokenStream) -> String {
+        let mut tokens = tokens.clone().into_iter();
+        let Some(first) = tokens.next() else {
+            return String::new();
+        };
+        let last = tokens.last().unwrap_or_else(|| first.clone());
+        let path = self.locate(first.span()).path;
+        let start = span_of(&first).byte_range().start;
+        let end = span_of(&last).byte_range().end;
+        self.text(&path)
+            .get(start..end)
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn text(&mut self, path: &Path) -> &str {
+        self.texts.entry(path.to_path_buf()).or_insert_with(|| {
+            self.overlay
+                .get(path)
+                .cloned()
+                .or_else(|| fs::read_to_string(path).ok())
+                .unwrap_or_default()
+        })
+    }
+}
+
+pub fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// LSP positions count UTF-16 units; proc-macro2 counts chars from line 1.
+pub fn position(text: &str, line: usize, column: usize) -> Position {
+    let units = text
+        .lines()
+        .nth(line.saturating_sub(1))
+        .map_or(column, |line| {
+            line.chars().take(column).map(char::len_utf16).sum()
+        });
+    Position::new(line.saturating_sub(1) as u32, units as u32)
+}
+
+fn span_of(tt: &TokenTree) -> Span {
+    match tt {
+        TokenTree::Group(group) => group.span(),
+        tt => tt.span(),
+    }
+}
+
+/// The body of the first `lunch! { .. }` in a Rust file.
+fn invocation_body(tokens: TokenStream) -> Option<TokenStream> {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    tokens.iter().enumerate().find_map(|(i, tt)| {
+        let TokenTree::Group(group) = tt else {
+            return None;
+        };
+        let invoked = i >= 2
+            && matches!(&tokens[i - 1], TokenTree::Punct(p) if p.as_char() == '!')
+            && matches!(&tokens[i - 2], TokenTree::Ident(ident) if ident == "lunch");
+        if invoked {
+            Some(group.stream())
+        } else {
+            invocation_body(group.stream())
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{crate_dir, open, position_of};
+
+    const DEMO: &str = r#"component = demo {
+    operations {
+        operation size {
+            do measure output size
+        }
+    }
+
+    signals {
+        include "families/shape.lunch"
+    }
+
+    clinch {
+        /* 🦀 */ when signal.shape.wide {
+            tag += "wide"
+        }
+    }
+}
+"#;
+
+    const SHAPE: &str = r#"family shape {
+    signal long {
+        derive from operation.size.measure
+            when size.len > 3
+    }
+
+    signal short {
+        derive from operation.size.measured
+            when size.len < 3
+    }
+}
+"#;
+
+    fn messages(diagnostics: &[Diagnostic]) -> Vec<(&str, Range)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.message.as_str(), d.range))
+            .collect()
+    }
+
+    #[test]
+    fn diagnostics_count_utf16_units() {
+        let dir = crate_dir("utf16", &[]);
+        let (root, analysis) = open(
+            &dir,
+            &[("demo.lunch", DEMO), ("families/shape.lunch", SHAPE)],
+        );
+
+        let start = position_of(DEMO, "wide");
+        let end = Position::new(start.line, start.character + 4);
+        let diagnostics = messages(&analysis.diagnostics[&root]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].1, Range::new(start, end));
+    }
+
+    #[test]
+    fn diagnostics_in_an_included_file_land_in_that_file() {
+        let dir = crate_dir("included", &[]);
+        let (_, analysis) = open(
+            &dir,
+            &[("demo.lunch", DEMO), ("families/shape.lunch", SHAPE)],
+        );
+
+        let shape = dir.join("families/shape.lunch");
+        assert_eq!(analysis.files, std::slice::from_ref(&shape));
+        let start = position_of(SHAPE, "measured");
+        let end = Position::new(start.line, start.character + 8);
+        let diagnostics = messages(&analysis.diagnostics[&shape]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].1, Range::new(start, end));
+    }
+
+    #[test]
+    fn invocation_in_a_rust_file_is_analyzed() {
+        let dir = crate_dir("invocation", &[]);
+        let family = SHAPE.replace("measured", "measure");
+        let body = DEMO.replace(r#"include "families/shape.lunch""#, &family);
+        let text = format!("lunch! {{\n    {}}}\n", body);
+        let (root, analysis) = open(&dir, &[("src/demo.rs", &text)]);
+
+        assert!(analysis.files.is_empty());
+        let start = position_of(&text, "wide");
+        let diagnostics = messages(&analysis.diagnostics[&root]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].1.start, start);
+        assert_eq!(analysis.model.unwrap().component, "demo");
+    }
+}
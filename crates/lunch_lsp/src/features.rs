@@ -0,0 +1,381 @@
+/*
+
+Hover, go-to-definition and completion, answered from the last good `Model` of
+the file's root. Completion also looks at the buffer text before the cursor,
+since what's being typed usually doesn't parse yet.
+
+*/
+
+use lsp_types::{CompletionItem, CompletionItemKind, Position};
+use std::path::Path;
+
+use crate::{
+    model::{Location, Model, Target},
+    roots::ops_function,
+};
+
+const ACTIONS: &[(&str, &str)] = &[
+    ("emit", "emit <Event> [{ field = value, .. }]"),
+    ("tag", "tag += <value> | tag <key> = <value>"),
+    ("score", "score <key> <op> <value>"),
+    ("run deferred", "run deferred <Handler>"),
+    ("run pipeline", "run pipeline <component>"),
+    ("verdict", "verdict <level>"),
+    ("halt", "halt"),
+];
+
+pub fn hover(model: &Model, path: &Path, position: Position) -> Option<String> {
+    let reference = model.at(path, position)?;
+    let text = match &reference.target {
+        Target::Signal(family, name) => {
+            let signal = model.signal(family, name)?;
+            let (op, step) = &signal.derive;
+            let mut lines = signal.when.lines().map(str::trim);
+            let mut text = format!(
+                "signal {}.{}\n    derive from operation.{}.{}\n    when {}",
+                family,
+                name,
+                op,
+                step,
+                lines.next().unwrap_or_default()
+            );
+            for line in lines {
+                text.push_str(&format!("\n        {}", line));
+            }
+            text
+        }
+        Target::Family(family) => {
+            let mut text = format!("family {}", family);
+            for signal in model.signals_of(family) {
+                text.push_str(&format!("\n    signal {}", signal.name));
+            }
+            text
+        }
+        Target::Operation(name) => {
+            let op = model.operation(name)?;
+            let mut text = format!("operation {}", op.name);
+            for step in &op.steps {
+                text.push_str(&format!("\n    do {} output {}", step.name, step.binding));
+            }
+            text
+        }
+        Target::Step(op, name) => {
+            let step = model
+                .operation(op)?
+                .steps
+                .iter()
+                .find(|s| s.name == *name)?;
+            format!(
+                "operation {}\n    do {} output {}\n\n// crate::{}_ops::{}",
+                op, step.name, step.binding, model.component, step.name
+            )
+        }
+        Target::Binding(binding) => {
+            let (op, step) = model.binding(binding)?;
+            format!(
+                "{}\n// output of operation.{}.{}",
+                binding, op.name, step.name
+            )
+        }
+    };
+    Some(format!("```lunch\n{}\n```", text))
+}
+
+/// Signal paths go to the signal, step paths to the Rust function behind the
+/// step when it can be found, and to the `do` line otherwise.
+pub fn definition(model: &Model, path: &Path, position: Position) -> Option<Location> {
+    let reference = model.at(path, position)?;
+    match &reference.target {
+        Target::Family(family) => Some(model.family(family)?.def.clone()),
+        Target::Signal(family, name) => Some(model.signal(family, name)?.def.clone()),
+        Target::Operation(name) => Some(model.operation(name)?.def.clone()),
+        Target::Step(op, name) => {
+            if let Some((path, range)) = ops_function(&model.manifest_dir, &model.component, name) {
+                return Some(Location { path, range });
+            }
+            let step = model
+                .operation(op)?
+                .steps
+                .iter()
+                .find(|s| s.name == *name)?;
+            Some(step.def.clone())
+        }
+        Target::Binding(binding) => Some(model.binding(binding)?.1.binding_def.clone()),
+    }
+}
+
+pub fn completion(model: &Model, text: &str, position: Position) -> Vec<CompletionItem> {
+    let before = text_before(text, position);
+    let before = before.trim_end_matches(is_word);
+
+    match trailing_path(before).as_slice() {
+        ["signal"] => model
+            .families
+            .iter()
+            .map(|f| item(&f.name, CompletionItemKind::MODULE, "family"))
+            .collect(),
+        ["signal", family] => model
+            .signals_of(family)
+            .map(|s| {
+                let detail = format!("derive from operation.{}.{}", s.derive.0, s.derive.1);
+                item(&s.name, CompletionItemKind::FIELD, &detail)
+            })
+            .collect(),
+        ["operation"] => model
+            .operations
+            .iter()
+            .map(|o| item(&o.name, CompletionItemKind::MODULE, "operation"))
+            .collect(),
+        ["operation", op] => model
+            .operation(op)
+            .into_iter()
+            .flat_map(|o| &o.steps)
+            .map(|s| {
+                let detail = format!("output {}", s.binding);
+                item(&s.name, CompletionItemKind::FUNCTION, &detail)
+            })
+            .collect(),
+        // A field of a binding is Rust, not ours to complete.
+        [..] if before.trim_end().ends_with('.') => Vec::new(),
+        _ => {
+            let mut items: Vec<CompletionItem> = model
+                .operations
+                .iter()
+                .flat_map(|o| o.steps.iter().map(move |s| (o, s)))
+                .map(|(o, s)| {
+                    let detail = format!("output of operation.{}.{}", o.name, s.name);
+                    item(&s.binding, CompletionItemKind::VARIABLE, &detail)
+                })
+                .collect();
+            items.extend(
+                model
+                    .params
+                    .iter()
+                    .map(|p| item(p, CompletionItemKind::CONSTANT, "param")),
+            );
+            if in_clinch_clause(before) {
+                items.extend(
+                    ACTIONS
+                        .iter()
+                        .map(|(action, usage)| item(action, CompletionItemKind::KEYWORD, usage)),
+                );
+            }
+            items
+        }
+    }
+}
+
+fn item(label: &str, kind: CompletionItemKind, detail: &str) -> CompletionItem {
+    CompletionItem {
+        label: label.to_string(),
+        kind: Some(kind),
+        detail: Some(detail.to_string()),
+        ..CompletionItem::default()
+    }
+}
+
+/// The text up to `position`, which counts UTF-16 units.
+fn text_before(text: &str, position: Position) -> &str {
+    let mut offset = 0;
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        if i == position.line as usize {
+            let mut units = 0;
+            for (at, c) in line.char_indices() {
+                if units >= position.character as usize || c == '\n' {
+                    return &text[..offset + at];
+                }
+                units += c.len_utf16();
+            }
+            return &text[..offset + line.len()];
+        }
+        offset += line.len();
+    }
+    text
+}
+
+/// For text ending in `signal.format.`, `["signal", "format"]`: the words of a
+/// dotted path that ends in a dot, without the dots.
+fn trailing_path(before: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut rest = before;
+    while let Some(stripped) = rest.trim_end().strip_suffix('.') {
+        let stripped = stripped.trim_end();
+        let word_start = stripped.trim_end_matches(is_word).len();
+        let word = &stripped[word_start..];
+        if word.is_empty() {
+            break;
+        }
+        words.insert(0, word);
+        rest = &stripped[..word_start];
+        if words.len() == 2 {
+            break;
+        }
+    }
+    words
+}
+
+/// Whether the innermost open brace is a clause directly inside `clinch { }`.
+fn in_clinch_clause(before: &str) -> bool {
+    let mut stack: Vec<String> = Vec::new();
+    let mut word = String::new();
+    let mut last_word = String::new();
+    let mut chars = before.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if is_word(c) {
+            word.push(c);
+            continue;
+        }
+        if !word.is_empty() {
+            last_word = std::mem::take(&mut word);
+        }
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                chars.find(|&c| c == '\n');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut depth = 1;
+                while depth > 0 {
+                    match chars.next() {
+                        Some('*') if chars.peek() == Some(&'/') => {
+                            chars.next();
+                            depth -= 1;
+                        }
+                        Some('/') if chars.peek() == Some(&'*') => {
+                            chars.next();
+                            depth += 1;
+                        }
+                        Some(_) => {}
+                        None => break,
+                    }
+                }
+            }
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '{' => stack.push(std::mem::take(&mut last_word)),
+            '}' => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+
+    stack.len() >= 2 && stack[stack.len() - 2] == "clinch"
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{crate_dir, open, position_of};
+    use std::path::PathBuf;
+
+    const DEMO: &str = r#"component = demo {
+    operations {
+        operation size {
+            do measure output size
+            do weigh output weight
+        }
+    }
+
+    signals {
+        family shape {
+            signal long {
+                derive from operation.size.measure
+                    when size.len > 3
+                      && size.len < 64
+            }
+        }
+    }
+
+    clinch {
+        when signal.shape.long {
+            tag += "long"
+        }
+    }
+}
+"#;
+
+    /// A comment and a string that mention `fn measure(` come before the real one.
+    const OPS: &str = r#"// fn measure(artifact) is below
+const NOTE: &str = "fn measure(";
+
+pub struct Size {
+    pub len: usize,
+}
+
+pub mod sizes {
+    use super::Size;
+
+    pub fn measure(artifact: &common::data_objs::Artifact) -> Size {
+        Size { len: artifact.bytes.len() }
+    }
+}
+"#;
+
+    fn model(test: &str) -> (PathBuf, Model) {
+        let dir = crate_dir(test, &[("src/demo_ops.rs", OPS)]);
+        let (root, analysis) = open(&dir, &[("demo.lunch", DEMO)]);
+        (root, analysis.model.expect("demo parses"))
+    }
+
+    #[test]
+    fn hover_on_a_signal_shows_its_definition() {
+        let (root, model) = model("hover_signal");
+        let at = position_of(DEMO, "long {\n            tag");
+
+        assert_eq!(
+            hover(&model, &root, at).as_deref(),
+            Some(
+                "```lunch\nsignal shape.long\n    derive from operation.size.measure\n    \
+                 when size.len > 3\n        && size.len < 64\n```"
+            )
+        );
+    }
+
+    #[test]
+    fn hover_on_a_family_lists_its_signals() {
+        let (root, model) = model("hover_family");
+        let at = position_of(DEMO, "shape {");
+
+        assert_eq!(
+            hover(&model, &root, at).as_deref(),
+            Some("```lunch\nfamily shape\n    signal long\n```")
+        );
+    }
+
+    #[test]
+    fn step_goes_to_the_rust_function() {
+        let (root, model) = model("definition_fn");
+        let at = position_of(DEMO, "measure\n                    when");
+
+        let location = definition(&model, &root, at).unwrap();
+        assert_eq!(location.path, model.manifest_dir.join("src/demo_ops.rs"));
+        let start = position_of(OPS, "measure(artifact: &");
+        assert_eq!(location.range.start, start);
+        assert_eq!(location.range.end.character, start.character + 7);
+    }
+
+    #[test]
+    fn step_without_a_function_goes_to_its_do_line() {
+        let (root, model) = model("definition_do");
+        let at = position_of(DEMO, "weigh");
+
+        let location = definition(&model, &root, at).unwrap();
+        assert_eq!(location.path, root);
+        assert_eq!(location.range.start, at);
+    }
+}
@@ -0,0 +1,368 @@
+/*
+
+lunch_lsp: a language server for lunch rule files, over stdio. It runs the same
+front end as `lunch!` (lunch_core) on every change and offers diagnostics,
+go-to-definition, completion and hover. Everything is local, nothing is fetched.
+
+A `.lunch` file is either a root, starting with `component = ..`, or a fragment
+pulled in by some root's `include`. A fragment is analyzed through its root: the
+first root in the same crate that includes it, found when it is opened.
+
+*/
+
+mod analysis;
+mod features;
+mod model;
+mod roots;
+#[cfg(test)]
+mod testing;
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response, ResponseError};
+use lsp_types::{
+    CompletionOptions, CompletionResponse, GotoDefinitionResponse, Hover, HoverContents,
+    HoverProviderCapability, InitializeParams, MarkupContent, MarkupKind, OneOf,
+    PublishDiagnosticsParams, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
+    Url,
+    notification::{self, Notification as _},
+    request::{self, Request as _},
+};
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use analysis::{analyze, canonical};
+use model::{Location, Model};
+use roots::{candidate_roots, has_extension, is_root, manifest_dir};
+
+const USAGE: &str = "usage: lunch_lsp\n\nSpeaks the language server protocol on stdin and stdout.";
+
+fn main() {
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            // Editors commonly pass this; stdio is all there is.
+            "--stdio" => {}
+            other => {
+                eprintln!("unknown argument `{}`\n{}", other, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+
+    if let Err(err) = run() {
+        eprintln!("lunch_lsp: {}", err);
+        process::exit(2);
+    }
+}
+
+fn run() -> Result<()> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![".".to_string()]),
+            ..CompletionOptions::default()
+        }),
+        ..ServerCapabilities::default()
+    };
+    let params = connection.initialize(serde_json::to_value(capabilities)?)?;
+    let _params: InitializeParams = serde_json::from_value(params)?;
+
+    let mut server = Server::new(&connection);
+    for message in &connection.receiver {
+        match message {
+            Message::Request(req) => {
+                if connection.handle_shutdown(&req)? {
+                    break;
+                }
+                server.request(req)?;
+            }
+            Message::Notification(not) => server.notification(not)?,
+            Message::Response(_) => {}
+        }
+    }
+
+    // The writer thread only ends once every sender is gone.
+    drop(server);
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+struct Server<'a> {
+    connection: &'a Connection,
+    /// Open documents, by canonical path.
+    docs: HashMap<PathBuf, String>,
+    /// File to the root it is analyzed through. Roots map to themselves.
+    roots: HashMap<PathBuf, PathBuf>,
+    /// Last model of each root that parsed.
+    models: HashMap<PathBuf, Model>,
+    /// Files each root has published diagnostics for, to clear them later.
+    published: HashMap<PathBuf, HashSet<PathBuf>>,
+}
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Sync + Send>>;
+
+impl<'a> Server<'a> {
+    fn new(connection: &'a Connection) -> Self {
+        Self {
+            connection,
+            docs: HashMap::new(),
+            roots: HashMap::new(),
+            models: HashMap::new(),
+            published: HashMap::new(),
+        }
+    }
+
+    /// Answer `req`. Bad params get an error response, the server keeps going.
+    fn request(&mut self, req: Request) -> Result<()> {
+        let response = match self.respond(&req.method, req.params) {
+            Ok(result) => Response::new_ok(req.id, result),
+            Err(error) => Response {
+                id: req.id,
+                result: None,
+                error: Some(error),
+            },
+        };
+        self.connection.sender.send(response.into())?;
+        Ok(())
+    }
+
+    fn respond(
+        &mut self,
+        method: &str,
+        params: Value,
+    ) -> std::result::Result<Value, ResponseError> {
+        let result = match method {
+            request::HoverRequest::METHOD => {
+                let params = request_params::<request::HoverRequest>(params)?;
+                let doc = params.text_document_position_params;
+                let hover = self
+                    .model_for(&doc.text_document.uri)
+                    .and_then(|(path, model)| features::hover(model, &path, doc.position));
+                serde_json::to_value(hover.map(|value| Hover {
+                    contents: HoverContents::Markup(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value,
+                    }),
+                    range: None,
+                }))
+            }
+            request::GotoDefinition::METHOD => {
+                let params = request_params::<request::GotoDefinition>(params)?;
+                let doc = params.text_document_position_params;
+                let location = self
+                    .model_for(&doc.text_document.uri)
+                    .and_then(|(path, model)| features::definition(model, &path, doc.position))
+                    .and_then(to_lsp);
+                serde_json::to_value(location.map(GotoDefinitionResponse::Scalar))
+            }
+            request::Completion::METHOD => {
+                let params = request_params::<request::Completion>(params)?;
+                let doc = params.text_document_position;
+                let items = self
+                    .model_for(&doc.text_document.uri)
+                    .and_then(|(path, model)| {
+                        let text = self.docs.get(&path)?;
+                        Some(features::completion(model, text, doc.position))
+                    })
+                    .unwrap_or_default();
+                serde_json::to_value(CompletionResponse::Array(items))
+            }
+            _ => {
+                return Err(response_error(
+                    ErrorCode::MethodNotFound,
+                    format!("unsupported request `{}`", method),
+                ));
+            }
+        };
+        result.map_err(|err| response_error(ErrorCode::InternalError, err.to_string()))
+    }
+
+    /// Apply `not`. One with bad params is logged and dropped.
+    fn notification(&mut self, not: Notification) -> Result<()> {
+        match not.method.as_str() {
+            notification::DidOpenTextDocument::METHOD => {
+                let Some(params) =
+                    notification_params::<notification::DidOpenTextDocument>(not.params)
+                else {
+                    return Ok(());
+                };
+                if let Some(path) = file_path(&params.text_document.uri) {
+                    self.docs.insert(path.clone(), params.text_document.text);
+                    self.changed(&path)?;
+                }
+            }
+            notification::DidChangeTextDocument::METHOD => {
+                let Some(params) =
+                    notification_params::<notification::DidChangeTextDocument>(not.params)
+                else {
+                    return Ok(());
+                };
+                // Full sync: the last change holds the whole text.
+                let text = params.content_changes.into_iter().last().map(|c| c.text);
+                if let (Some(path), Some(text)) = (file_path(&params.text_document.uri), text) {
+                    self.docs.insert(path.clone(), text);
+                    self.changed(&path)?;
+                }
+            }
+            notification::DidSaveTextDocument::METHOD => {
+                let Some(params) =
+                    notification_params::<notification::DidSaveTextDocument>(not.params)
+                else {
+                    return Ok(());
+                };
+                if let Some(path) = file_path(&params.text_document.uri) {
+                    self.changed(&path)?;
+                }
+            }
+            notification::DidCloseTextDocument::METHOD => {
+                let Some(params) =
+                    notification_params::<notification::DidCloseTextDocument>(not.params)
+                else {
+                    return Ok(());
+                };
+                if let Some(path) = file_path(&params.text_document.uri) {
+                    self.docs.remove(&path);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Re-analyze whatever `path` belongs to.
+    fn changed(&mut self, path: &Path) -> Result<()> {
+        let text = self.text(path);
+        let root = if is_root(path, &text) {
+            Some(path.to_path_buf())
+        } else {
+            self.root_of(path)
+        };
+        match root {
+            Some(root) => self.analyze(&root),
+            None => Ok(()),
+        }
+    }
+
+    /// The root including `path`: the one it was last seen through, or the
+    /// first in the crate whose includes reach it.
+    fn root_of(&mut self, path: &Path) -> Option<PathBuf> {
+        if let Some(root) = self.roots.get(path).filter(|root| *root != path) {
+            return Some(root.clone());
+        }
+        for candidate in candidate_roots(&manifest_dir(path)) {
+            let candidate = canonical(&candidate);
+            let text = self.text(&candidate);
+            if candidate == path || !is_root(&candidate, &text) {
+                continue;
+            }
+            let analysis = analyze(&candidate, &text, &self.docs);
+            if analysis.files.iter().any(|file| file == path) {
+                return Some(candidate);
+            }
+        }
+        None
+    }
+
+    fn analyze(&mut self, root: &Path) -> Result<()> {
+        let text = self.text(root);
+        let analysis = analyze(root, &text, &self.docs);
+
+        self.roots.insert(root.to_path_buf(), root.to_path_buf());
+        for file in &analysis.files {
+            self.roots.insert(file.clone(), root.to_path_buf());
+        }
+        if let Some(model) = analysis.model {
+            self.models.insert(root.to_path_buf(), model);
+        }
+
+        // `lunch!` errors in .rs files already come from the compiler.
+        let mut published = HashSet::new();
+        for (path, diagnostics) in analysis.diagnostics {
+            if has_extension(&path, "lunch") {
+                self.publish(&path, diagnostics)?;
+                published.insert(path);
+            }
+        }
+        let stale = self
+            .published
+            .insert(root.to_path_buf(), published.clone())
+            .unwrap_or_default();
+        for path in stale.difference(&published) {
+            self.publish(path, Vec::new())?;
+        }
+        Ok(())
+    }
+
+    fn publish(&self, path: &Path, diagnostics: Vec<lsp_types::Diagnostic>) -> Result<()> {
+        let Ok(uri) = Url::from_file_path(path) else {
+            return Ok(());
+        };
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+        let not = Notification::new(notification::PublishDiagnostics::METHOD.to_string(), params);
+        self.connection.sender.send(not.into())?;
+        Ok(())
+    }
+
+    fn model_for(&self, uri: &Url) -> Option<(PathBuf, &Model)> {
+        let path = file_path(uri)?;
+        let model = self.models.get(self.roots.get(&path)?)?;
+        Some((path, model))
+    }
+
+    /// The open buffer, or what's on disk.
+    fn text(&self, path: &Path) -> String {
+        self.docs
+            .get(path)
+            .cloned()
+            .or_else(|| fs::read_to_string(path).ok())
+            .unwrap_or_default()
+    }
+}
+
+fn request_params<R: request::Request>(
+    params: Value,
+) -> std::result::Result<R::Params, ResponseError> {
+    serde_json::from_value(params).map_err(|err| {
+        response_error(
+            ErrorCode::InvalidParams,
+            format!("invalid `{}` params: {}", R::METHOD, err),
+        )
+    })
+}
+
+/// stderr is the client's log.
+fn notification_params<N: notification::Notification>(params: Value) -> Option<N::Params> {
+    serde_json::from_value(params)
+        .inspect_err(|err| eprintln!("lunch_lsp: dropped `{}`: {}", N::METHOD, err))
+        .ok()
+}
+
+fn response_error(code: ErrorCode, message: String) -> ResponseError {
+    ResponseError {
+        code: code as i32,
+        message,
+        data: None,
+    }
+}
+
+fn file_path(uri: &Url) -> Option<PathBuf> {
+    uri.to_file_path().ok().map(|path| canonical(&path))
+}
+
+fn to_lsp(location: Location) -> Option<lsp_types::Location> {
+    let uri = Url::from_file_path(&location.path).ok()?;
+    Some(lsp_types::Location::new(uri, location.range))
+}
@@ -0,0 +1,282 @@
+/*
+
+What the editor features need from a pipeline that parsed: its symbols, where
+they are defined, and every place one is referenced. Nothing here holds a span,
+so the last good model outlives the pass that built it and keeps answering
+hover and go-to-definition while the buffer is broken.
+
+References are found on the resolved tokens, not the AST, because `when`
+conditions and clinch payloads are kept as plain Rust tokens: `signal.<f>.<n>`
+and `operation.<o>.<s>` paths, and any identifier that names a step's output.
+
+*/
+
+use lsp_types::{Position, Range};
+use lunch_core::LunchGenInput;
+use proc_macro2::{Ident, TokenStream, TokenTree};
+use std::path::{Path, PathBuf};
+
+use crate::analysis::Locator;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub range: Range,
+}
+
+pub struct Model {
+    pub component: String,
+    pub manifest_dir: PathBuf,
+    pub families: Vec<FamilySymbol>,
+    pub operations: Vec<OperationSymbol>,
+    pub params: Vec<String>,
+    pub references: Vec<Reference>,
+}
+
+pub struct FamilySymbol {
+    pub name: String,
+    pub def: Location,
+    pub signals: Vec<SignalSymbol>,
+}
+
+pub struct SignalSymbol {
+    pub name: String,
+    pub def: Location,
+    /// `(operation, step)`
+    pub derive: (String, String),
+    pub when: String,
+}
+
+pub struct OperationSymbol {
+    pub name: String,
+    pub def: Location,
+    pub steps: Vec<StepSymbol>,
+}
+
+pub struct StepSymbol {
+    pub name: String,
+    pub binding: String,
+    pub def: Location,
+    pub binding_def: Location,
+}
+
+pub struct Reference {
+    pub location: Location,
+    pub target: Target,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    Family(String),
+    /// `(family, signal)`
+    Signal(String, String),
+    Operation(String),
+    /// `(operation, step)`
+    Step(String, String),
+    Binding(String),
+}
+
+impl Model {
+    pub fn build(
+        input: &LunchGenInput,
+        resolved: TokenStream,
+        manifest_dir: &Path,
+        locator: &mut Locator,
+    ) -> Self {
+        let pipeline = &input.pipeline;
+        let mut model = Model {
+            component: input.component.to_string(),
+            manifest_dir: manifest_dir.to_path_buf(),
+            families: Vec::new(),
+            operations: Vec::new(),
+            params: Vec::new(),
+            references: Vec::new(),
+        };
+
+        for (_, block) in pipeline.operation_blocks() {
+            for op in &block.definitions {
+                let steps = op
+                    .body
+                    .stmts
+                    .iter()
+                    .map(|stmt| StepSymbol {
+                        name: stmt.step.to_string(),
+                        binding: stmt.output.to_string(),
+                        def: locator.locate(stmt.step.span()),
+                        binding_def: locator.locate(stmt.output.span()),
+                    })
+                    .collect();
+                model.operations.push(OperationSymbol {
+                    name: op.name.to_string(),
+                    def: locator.locate(op.name.span()),
+                    steps,
+                });
+            }
+        }
+
+        for (_, block) in pipeline.signal_blocks() {
+            for family in &block.families {
+                let signals = family
+                    .signals
+                    .iter()
+                    .map(|signal| SignalSymbol {
+                        name: signal.name.to_string(),
+                        def: locator.locate(signal.name.span()),
+                        derive: (
+                            signal.body.derive_from.operation.to_string(),
+                            signal.body.derive_from.step.to_string(),
+                        ),
+                        when: locator.source(&signal.body.when),
+                    })
+                    .collect();
+                model.families.push(FamilySymbol {
+                    name: family.name.to_string(),
+                    def: locator.locate(family.name.span()),
+                    signals,
+                });
+            }
+        }
+
+        if let Some(params) = &pipeline.params {
+            model.params = params.params.iter().map(|p| p.name.to_string()).collect();
+        }
+
+        model.collect_definitions();
+        model.collect_references(resolved, locator);
+        model
+    }
+
+    /// Definitions count as references to themselves, so hover works on them.
+    fn collect_definitions(&mut self) {
+        let mut references = Vec::new();
+        for family in &self.families {
+            references.push(Reference {
+                location: family.def.clone(),
+                target: Target::Family(family.name.clone()),
+            });
+            for signal in &family.signals {
+                references.push(Reference {
+                    location: signal.def.clone(),
+                    target: Target::Signal(family.name.clone(), signal.name.clone()),
+                });
+            }
+        }
+        for op in &self.operations {
+            references.push(Reference {
+                location: op.def.clone(),
+                target: Target::Operation(op.name.clone()),
+            });
+            for step in &op.steps {
+                references.push(Reference {
+                    location: step.def.clone(),
+                    target: Target::Step(op.name.clone(), step.name.clone()),
+                });
+                references.push(Reference {
+                    location: step.binding_def.clone(),
+                    target: Target::Binding(step.binding.clone()),
+                });
+            }
+        }
+        self.references = references;
+    }
+
+    fn collect_references(&mut self, tokens: TokenStream, locator: &mut Locator) {
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        let mut i = 0;
+        while i < tokens.len() {
+            if let TokenTree::Group(group) = &tokens[i] {
+                self.collect_references(group.stream(), locator);
+                i += 1;
+                continue;
+            }
+            let TokenTree::Ident(ident) = &tokens[i] else {
+                i += 1;
+                continue;
+            };
+            let after_dot = i > 0 && is_dot(&tokens[i - 1]);
+            let first = path_segment(&tokens, i + 1);
+            let second = first.and_then(|_| path_segment(&tokens, i + 3));
+
+            match (ident.to_string().as_str(), first, second) {
+                ("signal", Some(family), second) if !after_dot => {
+                    self.reference(locator, family, Target::Family(family.to_string()));
+                    if let Some(name) = second {
+                        let target = Target::Signal(family.to_string(), name.to_string());
+                        self.reference(locator, name, target);
+                    }
+                    i += if second.is_some() { 5 } else { 3 };
+                }
+                ("operation", Some(op), second) if !after_dot => {
+                    self.reference(locator, op, Target::Operation(op.to_string()));
+                    if let Some(step) = second {
+                        let target = Target::Step(op.to_string(), step.to_string());
+                        self.reference(locator, step, target);
+                    }
+                    i += if second.is_some() { 5 } else { 3 };
+                }
+                (name, _, _) if !after_dot && self.binding(name).is_some() => {
+                    self.reference(locator, ident, Target::Binding(name.to_string()));
+                    i += 1;
+                }
+                _ => i += 1,
+            }
+        }
+    }
+
+    fn reference(&mut self, locator: &mut Locator, ident: &Ident, target: Target) {
+        let location = locator.locate(ident.span());
+        if !self.references.iter().any(|r| r.location == location) {
+            self.references.push(Reference { location, target });
+        }
+    }
+
+    pub fn at(&self, path: &Path, position: Position) -> Option<&Reference> {
+        self.references.iter().find(|r| {
+            r.location.path == path
+                && r.location.range.start <= position
+                && position <= r.location.range.end
+        })
+    }
+
+    pub fn family(&self, name: &str) -> Option<&FamilySymbol> {
+        self.families.iter().find(|f| f.name == name)
+    }
+
+    pub fn signal(&self, family: &str, name: &str) -> Option<&SignalSymbol> {
+        self.signals_of(family).find(|s| s.name == name)
+    }
+
+    pub fn operation(&self, name: &str) -> Option<&OperationSymbol> {
+        self.operations.iter().find(|o| o.name == name)
+    }
+
+    /// The operation and step that output `binding`.
+    pub fn binding(&self, binding: &str) -> Option<(&OperationSymbol, &StepSymbol)> {
+        self.operations.iter().find_map(|op| {
+            op.steps
+                .iter()
+                .find(|step| step.binding == binding)
+                .map(|step| (op, step))
+        })
+    }
+
+    /// Family names are unique across stages, so this is one family's signals.
+    pub fn signals_of(&self, family: &str) -> impl Iterator<Item = &SignalSymbol> {
+        self.family(family).into_iter().flat_map(|f| &f.signals)
+    }
+}
+
+fn is_dot(tt: &TokenTree) -> bool {
+    matches!(tt, TokenTree::Punct(p) if p.as_char() == '.')
+}
+
+/// The identifier in `. <ident>` at `i`.
+fn path_segment(tokens: &[TokenTree], i: usize) -> Option<&Ident> {
+    if !tokens.get(i).is_some_and(is_dot) {
+        return None;
+    }
+    match tokens.get(i + 1)? {
+        TokenTree::Ident(ident) => Some(ident),
+        _ => None,
+    }
+}
@@ -0,0 +1,117 @@
+/*
+
+Finding things on disk: the crate a rule file belongs to, the files in it that
+can hold a whole pipeline, and the Rust function behind a step.
+
+*/
+
+use lsp_types::Range;
+use proc_macro2::{Ident, TokenStream, TokenTree};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use syn::Item;
+
+use crate::analysis::position;
+
+/// The nearest directory above `path` with a Cargo.toml. `include` paths
+/// resolve against it, same as in `lunch!`.
+pub fn manifest_dir(path: &Path) -> PathBuf {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .or_else(|| path.parent())
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// Files under `dir` that may hold a whole pipeline: `.lunch` files, and `.rs`
+/// files with a `lunch!` invocation. `target` and hidden directories are skipped.
+pub fn candidate_roots(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    walk(dir, &mut files);
+    files.retain(|file| {
+        has_extension(file, "lunch")
+            || (has_extension(file, "rs")
+                && fs::read_to_string(file).is_ok_and(|text| text.contains("lunch!")))
+    });
+    files
+}
+
+/// Where `crate::<component>_ops::<step>` is defined. Ops files are often
+/// re-exports, so after `<component>_ops.rs` the rest of `src` is searched.
+pub fn ops_function(manifest_dir: &Path, component: &str, step: &str) -> Option<(PathBuf, Range)> {
+    let src = manifest_dir.join("src");
+    let ops_file = src.join(format!("{}_ops.rs", component));
+
+    let mut files = Vec::new();
+    walk(&src, &mut files);
+    files.retain(|file| has_extension(file, "rs") && *file != ops_file);
+    files.insert(0, ops_file);
+
+    files.into_iter().find_map(|file| {
+        let text = fs::read_to_string(&file).ok()?;
+        let range = find_fn(&text, step)?;
+        Some((file, range))
+    })
+}
+
+/// Whether `text` holds a whole pipeline rather than a fragment for `include`.
+/// Text that doesn't lex counts as a root, so the error gets reported.
+pub fn is_root(path: &Path, text: &str) -> bool {
+    if has_extension(path, "rs") {
+        return true;
+    }
+    match TokenStream::from_str(text) {
+        Ok(tokens) => tokens
+            .into_iter()
+            .next()
+            .is_some_and(|tt| matches!(tt, TokenTree::Ident(ident) if ident == "component")),
+        Err(_) => true,
+    }
+}
+
+pub fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension().is_some_and(|e| e == ext)
+}
+
+/// The name in `fn <name>` of `text`, at module level or in an inline `mod`.
+/// Text that doesn't parse as Rust has none.
+fn find_fn(text: &str, name: &str) -> Option<Range> {
+    let file = syn::parse_file(text).ok()?;
+    let span = fn_named(&file.items, name)?.span();
+    Some(Range::new(
+        position(text, span.start().line, span.start().column),
+        position(text, span.end().line, span.end().column),
+    ))
+}
+
+fn fn_named<'a>(items: &'a [Item], name: &str) -> Option<&'a Ident> {
+    items.iter().find_map(|item| match item {
+        Item::Fn(f) if f.sig.ident == name => Some(&f.sig.ident),
+        Item::Mod(m) => fn_named(&m.content.as_ref()?.1, name),
+        _ => None,
+    })
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    entries.sort();
+    for entry in entries {
+        let hidden = entry
+            .file_name()
+            .is_some_and(|name| name == "target" || name.to_string_lossy().starts_with('.'));
+        if entry.is_dir() {
+            if !hidden {
+                walk(&entry, files);
+            }
+        } else {
+            files.push(entry);
+        }
+    }
+}
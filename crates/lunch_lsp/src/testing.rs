@@ -0,0 +1,53 @@
+/*
+
+Helpers for the unit tests: a throwaway crate on disk, and documents analyzed
+from memory the way open editor buffers are.
+
+*/
+
+use lsp_types::Position;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::analysis::{Analysis, analyze, canonical};
+
+/// A fresh crate root holding `files` next to its Cargo.toml.
+pub fn crate_dir(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lunch_lsp_{}_{}", std::process::id(), test));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+
+    for (name, text) in files {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    canonical(&dir)
+}
+
+/// Analyze `docs[0]` as the root. Every doc is read from memory, none from disk.
+pub fn open(dir: &Path, docs: &[(&str, &str)]) -> (PathBuf, Analysis) {
+    let overlay: HashMap<PathBuf, String> = docs
+        .iter()
+        .map(|(name, text)| (dir.join(name), text.to_string()))
+        .collect();
+    let (root, text) = docs[0];
+    let root = dir.join(root);
+    let analysis = analyze(&root, text, &overlay);
+    (root, analysis)
+}
+
+/// Where the first `needle` in `text` starts, in UTF-16 units like LSP.
+pub fn position_of(text: &str, needle: &str) -> Position {
+    let at = text.find(needle).expect("needle is in the text");
+    let line_start = text[..at].rfind('\n').map_or(0, |i| i + 1);
+    Position::new(
+        text[..at].matches('\n').count() as u32,
+        text[line_start..at].encode_utf16().count() as u32,
+    )
+}
@@ -0,0 +1,92 @@
+/*
+
+Guards around formatting. A body has to parse before it's formatted, and the
+output has to lex to the same tokens as the input, so a formatter bug can't
+change what a rule pack means. Outside a proc macro proc-macro2 lexes on its
+own, so spans carry real lines and columns for the messages.
+
+*/
+
+use lunch_core::LunchGenInput;
+use proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree};
+use std::str::FromStr;
+use syn::Lit;
+
+pub fn lex(source: &str) -> Result<TokenStream, String> {
+    TokenStream::from_str(source)
+        .map_err(|err| format!("{}: not valid token syntax", location(err.span())))
+}
+
+/// Parse errors in every DSL body of the file. A `.lunch` file that doesn't
+/// start with `component` is a fragment for `include`; it can't be parsed on
+/// its own, so only its tokens are checked.
+pub fn parse_errors(tokens: &TokenStream, lunch_file: bool) -> Vec<String> {
+    let mut bodies = Vec::new();
+    if lunch_file {
+        bodies.push(tokens.clone());
+    } else {
+        find_invocations(tokens.clone(), &mut bodies);
+    }
+
+    bodies
+        .into_iter()
+        .filter(|body| matches!(body.clone().into_iter().next(), Some(TokenTree::Ident(ident)) if ident == "component"))
+        .filter_map(|body| syn::parse2::<LunchGenInput>(strip_includes(body)).err())
+        .flat_map(|err| {
+            err.into_iter()
+                .map(|e| format!("{}: {}", location(e.span()), e))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+pub fn same_tokens(tokens: &TokenStream, formatted: &str) -> bool {
+    lex(formatted).is_ok_and(|after| after.to_string() == tokens.to_string())
+}
+
+/// The bodies of `lunch! { .. }` invocations, wherever they are in the file.
+fn find_invocations(tokens: TokenStream, bodies: &mut Vec<TokenStream>) {
+    let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+    for (i, tt) in tokens.iter().enumerate() {
+        let TokenTree::Group(group) = tt else {
+            continue;
+        };
+        let invoked = i >= 2
+            && matches!(&tokens[i - 1], TokenTree::Punct(p) if p.as_char() == '!')
+            && matches!(&tokens[i - 2], TokenTree::Ident(ident) if ident == "lunch");
+        if invoked {
+            bodies.push(group.stream());
+        } else {
+            find_invocations(group.stream(), bodies);
+        }
+    }
+}
+
+/// Included files are spliced in before parsing, so an `include` is never
+/// something the parser has to accept.
+fn strip_includes(tokens: TokenStream) -> TokenStream {
+    let mut out = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(tt) = tokens.next() {
+        match tt {
+            TokenTree::Ident(ident)
+                if ident == "include"
+                    && matches!(tokens.peek(), Some(TokenTree::Literal(lit)) if matches!(Lit::new(lit.clone()), Lit::Str(_))) =>
+            {
+                tokens.next();
+            }
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                let mut stripped = Group::new(group.delimiter(), strip_includes(group.stream()));
+                stripped.set_span(group.span());
+                out.push(stripped.into());
+            }
+            tt => out.push(tt),
+        }
+    }
+    out.into_iter().collect()
+}
+
+fn location(span: Span) -> String {
+    let start = span.start();
+    format!("{}:{}", start.line, start.column + 1)
+}
@@ -0,0 +1,280 @@
+/*
+
+Canonical layout for a DSL body. Line breaks are kept as written; what changes:
+
+- Indentation is four spaces per open brace, bracket or paren. The brace after
+  `component = <name>` doesn't indent, so a body reads like a file.
+- A `when` that continues a signal or suppression goes one level deeper, and a
+  line starting with `&&` or `||` lines its operand up with the `when`'s. A
+  bracket opened in the condition indents from the `when`, and its lines don't
+  end the chain. In `clinch`, `when` starts a clause and indents like any line.
+- Runs of spaces in code become one space, and trailing whitespace goes.
+- Consecutive `do <step> output <binding>` lines align their `output`, and
+  consecutive `key = value` lines in `meta` align their `=`.
+- More than one blank line in a row becomes one.
+
+Block comments are shifted along with the line they start on. Strings are never
+touched, nothing is reordered.
+
+*/
+
+use crate::scan::{Line, State};
+
+const INDENT: usize = 4;
+
+struct Out {
+    indent: usize,
+    content: String,
+    align: Align,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Align {
+    None,
+    /// Width of the step name in a `do` line.
+    Do(usize),
+    /// Width of the key in a `meta` entry.
+    Meta(usize),
+}
+
+/// An open delimiter: whether it indents what's inside, and the word that came
+/// before it (`meta`, `operations`, ..).
+struct Open {
+    indents: bool,
+    block: Option<String>,
+}
+
+pub fn format(lines: &[Line], base: usize) -> Vec<String> {
+    let mut out: Vec<Out> = Vec::new();
+    let mut stack: Vec<Open> = Vec::new();
+    let mut words: Vec<String> = Vec::new();
+    // Indent of the `when` that opened a condition, and how many delimiters
+    // were open on its line.
+    let mut when_at: Option<(usize, usize)> = None;
+    let mut comment_shift: isize = 0;
+
+    for line in lines {
+        match line.starts_in {
+            State::BlockComment(_) => {
+                let lead = lead_width(line.text);
+                let indent = (lead as isize + comment_shift).max(0) as usize;
+                let content = line.text.trim();
+                out.push(Out {
+                    indent: if content.is_empty() { 0 } else { indent },
+                    content: content.to_string(),
+                    align: Align::None,
+                });
+            }
+            State::Str | State::RawStr(_) => out.push(Out {
+                indent: 0,
+                content: line.text.to_string(),
+                align: Align::None,
+            }),
+            State::Code if line.is_blank() => {
+                if out.last().is_some_and(|o| !o.content.is_empty()) {
+                    out.push(Out {
+                        indent: 0,
+                        content: String::new(),
+                        align: Align::None,
+                    });
+                }
+            }
+            State::Code => {
+                let content = normalize(line);
+                let closers = content
+                    .chars()
+                    .take_while(|c| matches!(c, '}' | ')' | ']') || c.is_whitespace())
+                    .filter(|c| !c.is_whitespace())
+                    .count();
+                let open = &stack[..stack.len().saturating_sub(closers)];
+                let mut indent = base + INDENT * open.iter().filter(|o| o.indents).count();
+
+                let code: String = line.code().collect();
+                let code = code.trim();
+                let first = code.split(|c: char| !is_word(c)).next().unwrap_or("");
+                // In `clinch`, `when` starts a clause; anywhere else it continues
+                // the signal or suppression above.
+                let in_clinch = stack.last().and_then(|o| o.block.as_deref()) == Some("clinch");
+                let chain = when_at.filter(|&(_, depth)| open.len() >= depth);
+                if code.is_empty() {
+                    // A comment-only line doesn't break a `when` chain.
+                } else if first == "when" && !in_clinch {
+                    indent += INDENT;
+                    when_at = Some((indent, stack.len()));
+                } else if code.starts_with("&&") || code.starts_with("||") {
+                    indent = chain.map_or(indent + INDENT, |(when, _)| when + 2);
+                } else if let Some((when, depth)) = chain
+                    && (open.len() > depth || closers > 0)
+                {
+                    // Inside, or closing, a bracket the condition opened.
+                    indent = when + INDENT * (open.len() - depth);
+                } else {
+                    when_at = None;
+                }
+
+                if line.ends_in != State::Code {
+                    comment_shift = indent as isize - lead_width(line.text) as isize;
+                }
+
+                let block = stack.last().and_then(|o| o.block.as_deref());
+                let align = align_of(&content, block);
+                out.push(Out {
+                    indent,
+                    content,
+                    align,
+                });
+            }
+        }
+
+        track(line, &mut stack, &mut words);
+    }
+
+    align(&mut out);
+
+    out.into_iter()
+        .map(|o| {
+            if o.content.is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", " ".repeat(o.indent), o.content)
+            }
+        })
+        .collect()
+}
+
+/// The line without its indentation, with runs of spaces in code collapsed and
+/// trailing whitespace removed. Doc comments keep theirs, it's part of the doc.
+fn normalize(line: &Line) -> String {
+    let mut content = String::new();
+    for piece in &line.pieces {
+        if piece.code {
+            let mut space = false;
+            for c in piece.text.chars() {
+                if c.is_whitespace() {
+                    space = true;
+                } else {
+                    if space && !content.is_empty() {
+                        content.push(' ');
+                    }
+                    space = false;
+                    content.push(c);
+                }
+            }
+            if space && !content.is_empty() {
+                content.push(' ');
+            }
+        } else {
+            content.push_str(piece.text);
+        }
+    }
+
+    let doc = line
+        .pieces
+        .last()
+        .is_some_and(|p| !p.code && (p.text.starts_with("///") || p.text.starts_with("//!")));
+    if line.ends_in == State::Code && !doc {
+        content.truncate(content.trim_end().len());
+    }
+    content
+}
+
+fn align_of(content: &str, block: Option<&str>) -> Align {
+    let parts: Vec<&str> = content.split(' ').collect();
+    if parts.len() >= 4 && parts[0] == "do" && parts[2] == "output" {
+        return Align::Do(parts[1].chars().count());
+    }
+    if block == Some("meta") && parts.len() >= 3 && parts[1] == "=" && parts[0].chars().all(is_word)
+    {
+        return Align::Meta(parts[0].chars().count());
+    }
+    Align::None
+}
+
+/// Pad each run of consecutive, equally indented lines of one kind to the
+/// widest name in the run.
+fn align(out: &mut [Out]) {
+    let mut i = 0;
+    while i < out.len() {
+        let kind = std::mem::discriminant(&out[i].align);
+        if out[i].align == Align::None {
+            i += 1;
+            continue;
+        }
+        let end = (i..out.len())
+            .find(|&j| {
+                std::mem::discriminant(&out[j].align) != kind || out[j].indent != out[i].indent
+            })
+            .unwrap_or(out.len());
+        let width = out[i..end]
+            .iter()
+            .map(|o| match o.align {
+                Align::Do(w) | Align::Meta(w) => w,
+                Align::None => 0,
+            })
+            .max()
+            .unwrap_or(0);
+
+        for o in &mut out[i..end] {
+            let (prefix, name_at) = match o.align {
+                Align::Do(_) => ("do ", 1),
+                _ => ("", 0),
+            };
+            let mut parts: Vec<&str> = o.content.split(' ').collect();
+            let name = format!("{:<width$}", parts[name_at], width = width);
+            parts[name_at] = &name;
+            let rest = parts[name_at..].join(" ");
+            o.content = format!("{}{}", prefix, rest);
+        }
+        i = end;
+    }
+}
+
+/// Push and pop delimiters for the code on `line`.
+fn track(line: &Line, stack: &mut Vec<Open>, words: &mut Vec<String>) {
+    let mut word = String::new();
+    for c in line.code() {
+        if is_word(c) {
+            word.push(c);
+            continue;
+        }
+        if !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        match c {
+            '{' | '(' | '[' => {
+                let component = c == '{'
+                    && words.len() >= 3
+                    && words[words.len() - 3] == "component"
+                    && words[words.len() - 2] == "=";
+                stack.push(Open {
+                    indents: !component,
+                    block: words.last().cloned(),
+                });
+                words.push(c.to_string());
+            }
+            '}' | ')' | ']' => {
+                stack.pop();
+                words.push(c.to_string());
+            }
+            c if c.is_whitespace() => {}
+            c => words.push(c.to_string()),
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    if words.len() > 3 {
+        words.drain(..words.len() - 3);
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+pub fn lead_width(text: &str) -> usize {
+    text.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { INDENT } else { 1 })
+        .sum()
+}
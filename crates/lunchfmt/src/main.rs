@@ -0,0 +1,239 @@
+/*
+
+lunchfmt: canonical layout for lunch DSL bodies, in `.lunch` files and in
+`lunch!` invocations inside `.rs` files. Only whitespace changes. See `layout`
+for the rules.
+
+*/
+
+mod check;
+mod layout;
+mod scan;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use scan::{Line, State, scan};
+
+const USAGE: &str = "usage: lunchfmt [--check] <file or directory>...";
+
+fn main() {
+    let mut check = false;
+    let mut paths = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--check" => check = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            flag if flag.starts_with('-') => {
+                eprintln!("unknown option `{}`\n{}", flag, USAGE);
+                process::exit(2);
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+
+    let mut files = Vec::new();
+    for path in &paths {
+        collect(path, &mut files);
+    }
+
+    let mut status = 0;
+    for file in &files {
+        match format_file(file) {
+            Ok(None) => {}
+            Ok(Some((_, line))) if check => {
+                println!("{}:{}: not formatted", file.display(), line);
+                status = status.max(1);
+            }
+            Ok(Some((formatted, _))) => {
+                if let Err(err) = fs::write(file, formatted) {
+                    eprintln!("{}: {}", file.display(), err);
+                    status = 2;
+                }
+            }
+            Err(errors) => {
+                for err in errors {
+                    eprintln!("{}:{}", file.display(), err);
+                }
+                status = 2;
+            }
+        }
+    }
+    process::exit(status);
+}
+
+/// `.lunch` and `.rs` files under `path`. `target` directories are skipped.
+fn collect(path: &Path, files: &mut Vec<PathBuf>) {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return;
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            if entry.file_name().is_none_or(|name| name != "target") {
+                collect(&entry, files);
+            }
+        } else if entry
+            .extension()
+            .is_some_and(|ext| ext == "lunch" || ext == "rs")
+        {
+            files.push(entry);
+        }
+    }
+}
+
+/// The formatted source and the first line that changes, or `None` if the file
+/// is already formatted. Errors are `line:col: message`.
+fn format_file(path: &Path) -> Result<Option<(String, usize)>, Vec<String>> {
+    let source = fs::read_to_string(path).map_err(|err| vec![format!(" {}", err)])?;
+    let lunch_file = path.extension().is_some_and(|ext| ext == "lunch");
+    let formatted = format_source(&source, lunch_file)?;
+    if formatted == source {
+        return Ok(None);
+    }
+
+    let line = source
+        .lines()
+        .zip(formatted.lines())
+        .position(|(a, b)| a != b)
+        .unwrap_or_else(|| source.lines().count().min(formatted.lines().count()));
+    Ok(Some((formatted, line + 1)))
+}
+
+/// `source` in canonical layout: a whole `.lunch` file, or Rust with `lunch!`
+/// invocations in it.
+fn format_source(source: &str, lunch_file: bool) -> Result<String, Vec<String>> {
+    let tokens = check::lex(source).map_err(|err| vec![err])?;
+
+    let errors = check::parse_errors(&tokens, lunch_file);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let lines = scan(source);
+    let formatted = if lunch_file {
+        layout::format(&lines, 0).join("\n")
+    } else {
+        format_rust(&lines)
+    };
+    if formatted != source && !check::same_tokens(&tokens, &formatted) {
+        return Err(vec![
+            " formatting would change tokens, left as is".to_string(),
+        ]);
+    }
+
+    Ok(formatted)
+}
+
+/// Format each `lunch!` invocation that starts a line; the rest is Rust and is
+/// left to rustfmt.
+fn format_rust(lines: &[Line]) -> String {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = &lines[i];
+        let code: String = line.code().collect();
+        if line.starts_in == State::Code
+            && code.trim_start().starts_with("lunch!")
+            && let Some(end) = invocation_end(lines, i)
+        {
+            out.extend(layout::format(
+                &lines[i..=end],
+                layout::lead_width(line.text),
+            ));
+            i = end + 1;
+            continue;
+        }
+        out.push(line.text.to_string());
+        i += 1;
+    }
+    out.join("\n")
+}
+
+/// The line where the delimiter opened on line `start` closes.
+fn invocation_end(lines: &[Line], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        for c in line.code() {
+            match c {
+                '{' | '(' | '[' => depth += 1,
+                '}' | ')' | ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(i);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `tests/golden/<case>.<ext>.in` formats to `<case>.<ext>`, and the output
+    /// is left alone, by the formatter and by `--check`. The inputs don't end in
+    /// `.lunch` or `.rs`, so running lunchfmt over the repo leaves them be.
+    fn golden(case: &str, ext: &str) {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+        let read = |name: String| fs::read_to_string(dir.join(name)).unwrap();
+        let input = read(format!("{}.{}.in", case, ext));
+        let expected = read(format!("{}.{}", case, ext));
+        let lunch_file = ext == "lunch";
+
+        assert_eq!(format_source(&input, lunch_file).unwrap(), expected);
+        assert_eq!(format_source(&expected, lunch_file).unwrap(), expected);
+        assert_eq!(
+            format_file(&dir.join(format!("{}.{}", case, ext))),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn nested_blocks() {
+        golden("nested_blocks", "lunch");
+    }
+
+    #[test]
+    fn one_line_when() {
+        golden("one_line_when", "lunch");
+    }
+
+    #[test]
+    fn and_chains() {
+        golden("and_chains", "lunch");
+    }
+
+    #[test]
+    fn comments() {
+        golden("comments", "lunch");
+    }
+
+    #[test]
+    fn invocation_in_rust() {
+        golden("invocation", "rs");
+    }
+
+    #[test]
+    fn unbalanced_input_is_refused() {
+        assert!(format_source("component = demo { signals { family } ", true).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+/*
+
+Line scanner. The formatter only moves whitespace, so it works on source lines
+instead of a syntax tree. Each line is split into code and non-code pieces
+(comments, string and char literals), and records the state it starts in, so a
+line inside a block comment or a multi-line string is never mistaken for code.
+
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Code,
+    /// Inside `/* */`, with the nesting depth.
+    BlockComment(u32),
+    Str,
+    /// Inside `r#".."#`, with the number of `#`s.
+    RawStr(u32),
+}
+
+#[derive(Debug)]
+pub struct Piece<'a> {
+    pub code: bool,
+    pub text: &'a str,
+}
+
+#[derive(Debug)]
+pub struct Line<'a> {
+    pub text: &'a str,
+    pub starts_in: State,
+    pub ends_in: State,
+    pub pieces: Vec<Piece<'a>>,
+}
+
+impl Line<'_> {
+    /// The code on the line, comments and literals left out.
+    pub fn code(&self) -> impl Iterator<Item = char> + '_ {
+        self.pieces
+            .iter()
+            .filter(|piece| piece.code)
+            .flat_map(|piece| piece.text.chars())
+    }
+
+    pub fn is_blank(&self) -> bool {
+        self.starts_in == State::Code && self.text.trim().is_empty()
+    }
+}
+
+pub fn scan(source: &str) -> Vec<Line<'_>> {
+    let mut state = State::Code;
+    source
+        .split('\n')
+        .map(|text| {
+            let text = text.strip_suffix('\r').unwrap_or(text);
+            let starts_in = state;
+            let pieces = scan_line(text, &mut state);
+            Line {
+                text,
+                starts_in,
+                ends_in: state,
+                pieces,
+            }
+        })
+        .collect()
+}
+
+fn scan_line<'a>(text: &'a str, state: &mut State) -> Vec<Piece<'a>> {
+    let bytes = text.as_bytes();
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    let cut = |pieces: &mut Vec<Piece<'a>>, start: &mut usize, at: usize, code: bool| {
+        if at > *start {
+            pieces.push(Piece {
+                code,
+                text: &text[*start..at],
+            });
+        }
+        *start = at;
+    };
+
+    while i < bytes.len() {
+        match *state {
+            State::Code => match bytes[i] {
+                b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                    cut(&mut pieces, &mut start, i, true);
+                    i = bytes.len();
+                    cut(&mut pieces, &mut start, i, false);
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                    cut(&mut pieces, &mut start, i, true);
+                    *state = State::BlockComment(1);
+                    i += 2;
+                }
+                b'"' => {
+                    cut(&mut pieces, &mut start, i, true);
+                    *state = State::Str;
+                    i += 1;
+                }
+                b'r' if !ident_before(bytes, i) || bytes[i - 1] == b'b' => {
+                    let hashes = bytes[i + 1..].iter().take_while(|b| **b == b'#').count();
+                    if bytes.get(i + 1 + hashes) == Some(&b'"') {
+                        cut(&mut pieces, &mut start, i, true);
+                        *state = State::RawStr(hashes as u32);
+                        i += hashes + 2;
+                    } else {
+                        i += 1;
+                    }
+                }
+                b'\'' => match char_literal_len(&text[i..]) {
+                    Some(len) => {
+                        cut(&mut pieces, &mut start, i, true);
+                        i += len;
+                        cut(&mut pieces, &mut start, i, false);
+                    }
+                    None => i += 1,
+                },
+                _ => i += 1,
+            },
+            State::BlockComment(depth) => {
+                if bytes[i..].starts_with(b"/*") {
+                    *state = State::BlockComment(depth + 1);
+                    i += 2;
+                } else if bytes[i..].starts_with(b"*/") {
+                    i += 2;
+                    if depth == 1 {
+                        *state = State::Code;
+                        cut(&mut pieces, &mut start, i, false);
+                    } else {
+                        *state = State::BlockComment(depth - 1);
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            State::Str => match bytes[i] {
+                b'\\' => i += 2,
+                b'"' => {
+                    i += 1;
+                    *state = State::Code;
+                    cut(&mut pieces, &mut start, i, false);
+                }
+                _ => i += 1,
+            },
+            State::RawStr(hashes) => {
+                let end = 1 + hashes as usize;
+                if bytes[i] == b'"'
+                    && bytes[i + 1..]
+                        .iter()
+                        .take(end - 1)
+                        .filter(|b| **b == b'#')
+                        .count()
+                        == end - 1
+                {
+                    i += end;
+                    *state = State::Code;
+                    cut(&mut pieces, &mut start, i, false);
+                } else {
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    let i = i.min(bytes.len());
+    cut(&mut pieces, &mut start, i, *state == State::Code);
+    pieces
+}
+
+fn ident_before(bytes: &[u8], i: usize) -> bool {
+    i > 0 && (bytes[i - 1].is_ascii_alphanumeric() || bytes[i - 1] == b'_')
+}
+
+/// Length of the char literal at the start of `text`, or `None` for a lifetime.
+fn char_literal_len(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1);
+    match chars.next()? {
+        (_, '\\') => {
+            let (end, _) = chars.find(|(_, c)| *c == '\'')?;
+            Some(end + 1)
+        }
+        (_, _) => match chars.next()? {
+            (end, '\'') => Some(end + 1),
+            _ => None,
+        },
+    }
+}
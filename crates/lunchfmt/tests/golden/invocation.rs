@@ -0,0 +1,21 @@
+use lucius_macro::lunch;
+
+fn untouched()   {
+      let x = 1;
+}
+
+    lunch! {
+        component = demo
+
+        {
+        operations {
+            operation magic {
+                do inspect_magic output magic_probe
+            }
+        }
+
+        clinch {
+            when signal.format.pe_magic { score risk += 1.0 }
+        }
+        }
+    }